@@ -0,0 +1,383 @@
+//! Bit-level helpers over sequences of bytes.
+//!
+//! Unless stated otherwise, byte sequences are treated as big-endian bit strings: bit 0 is the
+//! most significant bit of the first byte, which is the convention used by CMAC and GCM.
+
+/// Counts the number of bits set in a sequence of bytes. The input is consumed 8 bytes at a time
+/// so that the hardware popcount instruction can be used whenever it's available.
+pub fn count_ones(bytes: impl AsRef<[u8]>) -> usize {
+    let bytes = bytes.as_ref();
+    let mut chunks = bytes.chunks_exact(8);
+    let mut res = 0;
+
+    for chunk in chunks.by_ref() {
+        res += u64::from_ne_bytes(chunk.try_into().unwrap()).count_ones() as usize;
+    }
+
+    res + chunks
+        .remainder()
+        .iter()
+        .map(|byte| byte.count_ones() as usize)
+        .sum::<usize>()
+}
+
+/// Returns the value of bit `i`. Panics if `i` is out of bounds.
+pub fn get_bit(bytes: impl AsRef<[u8]>, i: usize) -> bool {
+    bytes.as_ref()[i / 8] & (0x80 >> (i % 8)) != 0
+}
+
+/// Sets bit `i` to `value`. Panics if `i` is out of bounds.
+pub fn set_bit(mut bytes: impl AsMut<[u8]>, i: usize, value: bool) {
+    let bytes = bytes.as_mut();
+    if value {
+        bytes[i / 8] |= 0x80 >> (i % 8);
+    } else {
+        bytes[i / 8] &= !(0x80 >> (i % 8));
+    }
+}
+
+/// Flips bit `i`. Panics if `i` is out of bounds.
+pub fn flip_bit(mut bytes: impl AsMut<[u8]>, i: usize) {
+    bytes.as_mut()[i / 8] ^= 0x80 >> (i % 8);
+}
+
+/// Rotates a sequence of bytes `n` bits to the left, as if it was one big integer.
+pub fn rotate_left(bytes: impl AsRef<[u8]>, n: usize) -> Vec<u8> {
+    let bytes = bytes.as_ref();
+    let len = bytes.len();
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let n = n % (len * 8);
+    let (q, r) = (n / 8, n % 8);
+
+    (0..len)
+        .map(|i| {
+            let hi = bytes[(i + q) % len];
+            let lo = bytes[(i + q + 1) % len];
+            if r == 0 {
+                hi
+            } else {
+                (hi << r) | (lo >> (8 - r))
+            }
+        })
+        .collect()
+}
+
+/// Rotates a sequence of bytes `n` bits to the right, as if it was one big integer.
+pub fn rotate_right(bytes: impl AsRef<[u8]>, n: usize) -> Vec<u8> {
+    let bytes = bytes.as_ref();
+    if bytes.is_empty() {
+        return Vec::new();
+    }
+
+    let total = bytes.len() * 8;
+    rotate_left(bytes, total - n % total)
+}
+
+/// Shifts a sequence of bytes `n` bits to the left, as if it was one big integer. The bits
+/// shifted out are lost and zeros are shifted in.
+pub fn shift_left(bytes: impl AsRef<[u8]>, n: usize) -> Vec<u8> {
+    let bytes = bytes.as_ref();
+    let (q, r) = (n / 8, n % 8);
+    let get = |i: usize| bytes.get(i).copied().unwrap_or_default();
+
+    (0..bytes.len())
+        .map(|i| {
+            if r == 0 {
+                get(i + q)
+            } else {
+                (get(i + q) << r) | (get(i + q + 1) >> (8 - r))
+            }
+        })
+        .collect()
+}
+
+/// Shifts a sequence of bytes `n` bits to the right, as if it was one big integer. The bits
+/// shifted out are lost and zeros are shifted in.
+pub fn shift_right(bytes: impl AsRef<[u8]>, n: usize) -> Vec<u8> {
+    let bytes = bytes.as_ref();
+    let (q, r) = (n / 8, n % 8);
+    let get = |i: usize| {
+        i.checked_sub(q)
+            .and_then(|j| bytes.get(j))
+            .copied()
+            .unwrap_or_default()
+    };
+    let get_prev = |i: usize| {
+        i.checked_sub(q + 1)
+            .and_then(|j| bytes.get(j))
+            .copied()
+            .unwrap_or_default()
+    };
+
+    (0..bytes.len())
+        .map(|i| {
+            if r == 0 {
+                get(i)
+            } else {
+                (get(i) >> r) | (get_prev(i) << (8 - r))
+            }
+        })
+        .collect()
+}
+
+/// In-place version of [`shift_left`]. Returns the last bit that was shifted out, bit `n - 1`
+/// counting from the most significant one, or `false` if `n` is 0 or more than the number of bits.
+/// With `n == 1` that's the most significant bit, which is what CMAC subkey generation and
+/// doubling in GF(2^128) need to look at.
+pub fn shift_left_mut(mut bytes: impl AsMut<[u8]>, n: usize) -> bool {
+    let bytes = bytes.as_mut();
+    let carry = n > 0 && n <= bytes.len() * 8 && get_bit(&*bytes, n - 1);
+    let shifted = shift_left(&*bytes, n);
+    bytes.copy_from_slice(&shifted);
+    carry
+}
+
+/// In-place version of [`shift_right`]. Returns the last bit that was shifted out, bit `n - 1`
+/// counting from the least significant one, or `false` if `n` is 0 or more than the number of
+/// bits. With `n == 1` that's the least significant bit.
+pub fn shift_right_mut(mut bytes: impl AsMut<[u8]>, n: usize) -> bool {
+    let bytes = bytes.as_mut();
+    let total = bytes.len() * 8;
+    let carry = n > 0 && n <= total && get_bit(&*bytes, total - n);
+    let shifted = shift_right(&*bytes, n);
+    bytes.copy_from_slice(&shifted);
+    carry
+}
+
+/// The order in which the bits of each byte are read or written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The most significant bit of each byte comes first.
+    MsbFirst,
+    /// The least significant bit of each byte comes first.
+    LsbFirst,
+}
+
+impl BitOrder {
+    #[inline]
+    fn mask(self, i: usize) -> u8 {
+        match self {
+            BitOrder::MsbFirst => 0x80 >> (i % 8),
+            BitOrder::LsbFirst => 0x01 << (i % 8),
+        }
+    }
+}
+
+/// Reads a sequence of bytes one bit at a time.
+///
+/// ```
+/// use matasano_bitwise::bits::{BitOrder, BitReader};
+///
+/// let mut reader = BitReader::new(&[0b1010_0000], BitOrder::MsbFirst);
+/// assert_eq!(Some(0b101), reader.read_bits(3));
+/// assert_eq!(5, reader.remaining());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    order: BitOrder,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8], order: BitOrder) -> Self {
+        BitReader {
+            bytes,
+            pos: 0,
+            order,
+        }
+    }
+
+    /// Number of bits that haven't been read yet.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.pos
+    }
+
+    /// Reads the next bit, or returns `None` if all bits have been consumed.
+    pub fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.pos / 8)?;
+        let bit = byte & self.order.mask(self.pos) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    /// Reads the next `n` bits (at most 64) into an integer, the first bit read being the most
+    /// significant one. Returns `None` and doesn't consume anything if fewer than `n` bits are
+    /// left.
+    pub fn read_bits(&mut self, n: usize) -> Option<u64> {
+        assert!(n <= 64, "can't read more than 64 bits at once");
+        if n > self.remaining() {
+            return None;
+        }
+
+        let mut res = 0_u64;
+        for _ in 0..n {
+            res = (res << 1) | u64::from(self.read_bit()?);
+        }
+
+        Some(res)
+    }
+}
+
+impl Iterator for BitReader<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        self.read_bit()
+    }
+}
+
+/// Builds a sequence of bytes one bit at a time. The last byte is padded with zeros if the
+/// number of bits written isn't a multiple of 8.
+#[derive(Debug, Clone)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    len: usize,
+    order: BitOrder,
+}
+
+impl BitWriter {
+    pub fn new(order: BitOrder) -> Self {
+        BitWriter {
+            bytes: Vec::new(),
+            len: 0,
+            order,
+        }
+    }
+
+    /// Number of bits written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.len.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= self.order.mask(self.len);
+        }
+        self.len += 1;
+    }
+
+    /// Writes the `n` least significant bits of `value` (at most 64), most significant first.
+    pub fn write_bits(&mut self, value: u64, n: usize) {
+        assert!(n <= 64, "can't write more than 64 bits at once");
+        for i in (0..n).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::count_bits_set;
+
+    #[test]
+    fn test_count_ones() {
+        let bytes = (0..=u8::MAX).collect::<Vec<_>>();
+        assert_eq!(
+            bytes.iter().map(|b| count_bits_set(*b)).sum::<usize>(),
+            count_ones(&bytes)
+        );
+        assert_eq!(0, count_ones([]));
+    }
+
+    #[test]
+    fn test_get_set_flip_bit() {
+        let mut bytes = [0_u8; 2];
+        set_bit(&mut bytes, 0, true);
+        set_bit(&mut bytes, 15, true);
+        assert_eq!([0x80, 0x01], bytes);
+        assert!(get_bit(bytes, 15));
+
+        flip_bit(&mut bytes, 0);
+        set_bit(&mut bytes, 15, false);
+        assert_eq!([0x00, 0x00], bytes);
+    }
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(vec![0x23, 0x41], rotate_left([0x12, 0x34], 4));
+        assert_eq!(vec![0x68, 0x24], rotate_left([0x12, 0x34], 9));
+        assert_eq!(vec![0x12, 0x34], rotate_left([0x12, 0x34], 16));
+        assert_eq!(vec![0x41, 0x23], rotate_right([0x12, 0x34], 4));
+        assert_eq!(
+            vec![0x12, 0x34],
+            rotate_right(rotate_left([0x12, 0x34], 11), 11)
+        );
+    }
+
+    #[test]
+    fn test_shift() {
+        assert_eq!(vec![0x23, 0x40], shift_left([0x12, 0x34], 4));
+        assert_eq!(vec![0x68, 0x00], shift_left([0x12, 0x34], 9));
+        assert_eq!(vec![0x01, 0x23], shift_right([0x12, 0x34], 4));
+        assert_eq!(vec![0x00, 0x09], shift_right([0x12, 0x34], 9));
+        assert_eq!(vec![0x00, 0x00], shift_right([0x12, 0x34], 16));
+    }
+
+    #[test]
+    fn test_shift_left_mut_carry() {
+        // CMAC subkey generation from RFC 4493, starting from L = AES-128(K, 0^128).
+        let mut k1 = matasano_util::hex_str_to_bytes("7df76b0c1ab899b33e42f047b91b546f").unwrap();
+        if shift_left_mut(&mut k1, 1) {
+            k1[15] ^= 0x87;
+        }
+        assert_eq!(
+            matasano_util::hex_str_to_bytes("fbeed618357133667c85e08f7236a8de").unwrap(),
+            k1
+        );
+
+        let mut k2 = k1.clone();
+        if shift_left_mut(&mut k2, 1) {
+            k2[15] ^= 0x87;
+        }
+        assert_eq!(
+            matasano_util::hex_str_to_bytes("f7ddac306ae266ccf90bc11ee46d513b").unwrap(),
+            k2
+        );
+
+        let mut bytes = [0x81];
+        assert!(shift_right_mut(&mut bytes, 1));
+        assert_eq!([0x40], bytes);
+
+        // The last bit shifted out, not the outermost one.
+        assert!(shift_left_mut([0x40], 2));
+        assert!(!shift_left_mut([0x80], 2));
+        assert!(shift_right_mut([0x02], 2));
+        assert!(!shift_right_mut([0x01], 2));
+    }
+
+    #[test]
+    fn test_bit_reader_writer() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut writer = BitWriter::new(order);
+            writer.write_bits(0b101, 3);
+            writer.write_bits(0x1FF, 9);
+            assert_eq!(12, writer.len());
+
+            let bytes = writer.into_bytes();
+            let mut reader = BitReader::new(&bytes, order);
+            assert_eq!(Some(0b101), reader.read_bits(3));
+            assert_eq!(Some(0x1FF), reader.read_bits(9));
+            assert_eq!(Some(0), reader.read_bits(4));
+            assert_eq!(None, reader.read_bit());
+        }
+
+        let mut reader = BitReader::new(&[0x01], BitOrder::LsbFirst);
+        assert_eq!(Some(true), reader.read_bit());
+    }
+}
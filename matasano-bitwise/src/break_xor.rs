@@ -0,0 +1,238 @@
+//! Breakers for single-byte and repeating-key XOR.
+//!
+//! Every breaker takes the [`Scorer`] that ranks candidate plaintexts, so that they work for
+//! any language or format that can be scored. Higher scores are better.
+
+use matasano_constants::score::Scorer;
+use matasano_util::par;
+
+use crate::{period, xor_cycle};
+
+/// A candidate solution for single-byte XOR: the key, the plaintext and its score.
+pub type SingleByteCandidate = (u8, Vec<u8>, f64);
+
+/// A candidate solution for a XOR cipher: the key, the plaintext and its score.
+pub type Candidate = (Vec<u8>, Vec<u8>, f64);
+
+/// Tries every key against `ciphertext` and returns the best `k` candidates according to
+/// `scorer`, best first.
+pub fn rank_single_byte_xor(
+    ciphertext: impl AsRef<[u8]>,
+    scorer: &impl Scorer,
+    k: usize,
+) -> Vec<SingleByteCandidate> {
+    let ciphertext = ciphertext.as_ref();
+    let mut res = (0..=u8::MAX)
+        .map(|key| {
+            let plaintext = xor_cycle(ciphertext, [key]);
+            let score = scorer.score(&plaintext);
+            (key, plaintext, score)
+        })
+        .collect::<Vec<_>>();
+
+    res.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+    res.truncate(k);
+    res
+}
+
+/// The ciphertext, among many, that is most likely to be single-byte XOR encrypted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Detection {
+    /// Index of the ciphertext.
+    pub index: usize,
+    pub key: u8,
+    pub plaintext: Vec<u8>,
+    pub score: f64,
+    /// Difference between the score and the best score of any other ciphertext. Infinite if
+    /// there's only one ciphertext.
+    pub margin: f64,
+}
+
+/// Breaks every ciphertext as single-byte XOR and returns the one whose best candidate has the
+/// best score overall, or `None` if there are no ciphertexts.
+pub fn detect_single_byte_xor<T: AsRef<[u8]> + Sync>(
+    ciphertexts: &[T],
+    scorer: &(impl Scorer + Sync),
+) -> Option<Detection> {
+    let candidates = par::par_map(ciphertexts, |ciphertext| {
+        rank_single_byte_xor(ciphertext, scorer, 1).remove(0)
+    });
+
+    let (index, _) = candidates
+        .iter()
+        .enumerate()
+        .max_by(|(i, (_, _, a)), (j, (_, _, b))| a.total_cmp(b).then(j.cmp(i)))?;
+    let runner_up = candidates
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != index)
+        .map(|(_, (_, _, score))| *score)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let (key, plaintext, score) = candidates.into_iter().nth(index).unwrap();
+    Some(Detection {
+        index,
+        key,
+        plaintext,
+        score,
+        margin: score - runner_up,
+    })
+}
+
+/// Finds the single byte key that produces the best scoring output when XOR'd against `bytes`.
+/// Returns the key and the corresponding score.
+pub(crate) fn best_single_byte_key(bytes: &[u8], scorer: &impl Scorer) -> (u8, f64) {
+    let (key, _, score) = rank_single_byte_xor(bytes, scorer, 1).remove(0);
+    (key, score)
+}
+
+/// Most likely key sizes between 2 and 40, according to several independent tests: the best
+/// three by Hamming distance, the best three by index of coincidence and the best by Kasiski
+/// examination. Duplicates are removed.
+fn guess_keysizes(bytes: &[u8]) -> Vec<usize> {
+    let mut res = Vec::new();
+    let candidates = period::rank_by_hamming(bytes, 2..=40)
+        .into_iter()
+        .take(3)
+        .chain(
+            period::rank_by_index_of_coincidence(bytes, 2..=40)
+                .into_iter()
+                .take(3),
+        )
+        .chain(
+            period::rank_by_kasiski(bytes, 3, 2..=40)
+                .into_iter()
+                .take(1),
+        );
+
+    for (keysize, _) in candidates {
+        if !res.contains(&keysize) {
+            res.push(keysize);
+        }
+    }
+
+    res
+}
+
+/// Splits `bytes` in `keysize` columns, where the n-th column is made of every byte whose
+/// position modulo `keysize` is n.
+fn transpose(bytes: &[u8], keysize: usize) -> Vec<Vec<u8>> {
+    let mut res = vec![Vec::new(); keysize];
+
+    for (i, byte) in bytes.iter().enumerate() {
+        res[i % keysize].push(*byte);
+    }
+
+    res
+}
+
+/// Breaks a ciphertext that has been encrypted with repeating-key XOR. The most likely key sizes
+/// (see [`guess_keysizes`]) are tried and each of the transposed columns is solved as single byte
+/// XOR, with `scorer` ranking both the columns and the whole plaintexts. Returns a Vec of tuples
+/// `(key, plaintext, score)` sorted by score, with the best candidate first.
+pub fn break_repeating_key_xor(
+    ciphertext: impl AsRef<[u8]>,
+    scorer: &(impl Scorer + Sync),
+) -> Vec<Candidate> {
+    let ciphertext = ciphertext.as_ref();
+
+    let mut res = par::par_map(&guess_keysizes(ciphertext), |keysize| {
+        let key = transpose(ciphertext, *keysize)
+            .iter()
+            .map(|column| best_single_byte_key(column, scorer).0)
+            .collect::<Vec<_>>();
+        let plaintext = xor_cycle(ciphertext, &key);
+        let score = scorer.score(&plaintext);
+
+        (key, plaintext, score)
+    });
+
+    res.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use matasano_constants::score::{PrintableRatio, Weighted};
+
+    use super::*;
+
+    const PLAINTEXT: &str = include_str!("../../testdata/english.txt");
+
+    #[test]
+    fn test_rank_single_byte_xor() {
+        let ciphertext = xor_cycle("Cooking MC's like a pound of bacon", [0xA5]);
+        let candidates = rank_single_byte_xor(&ciphertext, &Weighted::english(), 3);
+
+        assert_eq!(3, candidates.len());
+        assert_eq!(0xA5, candidates[0].0);
+        assert!(candidates.windows(2).all(|w| w[0].2 >= w[1].2));
+        assert_eq!(
+            256,
+            rank_single_byte_xor(&ciphertext, &PrintableRatio, 1000).len()
+        );
+    }
+
+    #[test]
+    fn test_detect_single_byte_xor() {
+        let plaintext = &PLAINTEXT.as_bytes()[..60];
+        let mut ciphertexts = (0..20)
+            .map(|i| xor_cycle(&PLAINTEXT.as_bytes()[60..120], [i, 0x5A ^ i, 0xC3]))
+            .collect::<Vec<_>>();
+        ciphertexts[13] = xor_cycle(plaintext, [0x7E]);
+
+        let detection = detect_single_byte_xor(&ciphertexts, &Weighted::english()).unwrap();
+        assert_eq!((13, 0x7E), (detection.index, detection.key));
+        assert_eq!(plaintext, detection.plaintext.as_slice());
+        assert!(detection.margin > 0.0, "{}", detection.margin);
+
+        assert_eq!(
+            None,
+            detect_single_byte_xor::<Vec<u8>>(&[], &Weighted::english())
+        );
+        assert_eq!(
+            f64::INFINITY,
+            detect_single_byte_xor(&ciphertexts[..1], &Weighted::english())
+                .unwrap()
+                .margin
+        );
+    }
+
+    #[test]
+    fn test_best_single_byte_key() {
+        let ciphertext = xor_cycle(PLAINTEXT, [0x3C]);
+        assert_eq!(
+            0x3C,
+            best_single_byte_key(&ciphertext, &Weighted::english()).0
+        );
+    }
+
+    #[test]
+    fn test_guess_keysizes() {
+        let ciphertext = xor_cycle(
+            "I'm back and I'm ringin' the bell\nA rockin' on the mike while the fly girls \
+             yell\nIn ecstasy in the back of me\nWell that's my DJ Deshay cuttin' all them Z's",
+            "ICE ICE BABY",
+        );
+        assert!(guess_keysizes(&ciphertext).contains(&12));
+    }
+
+    #[test]
+    fn test_transpose() {
+        assert_eq!(
+            vec![vec![1, 4, 7], vec![2, 5], vec![3, 6]],
+            transpose(&[1, 2, 3, 4, 5, 6, 7], 3)
+        );
+    }
+
+    #[test]
+    fn test_break_repeating_key_xor() {
+        let ciphertext = xor_cycle(PLAINTEXT, "Harbour");
+        let candidates = break_repeating_key_xor(&ciphertext, &Weighted::english());
+        let (key, plaintext, _) = candidates.first().unwrap();
+
+        // A multiple of the key size decrypts just as well, with the key repeated.
+        assert!(key.chunks(7).all(|chunk| chunk == b"Harbour"), "{:?}", key);
+        assert_eq!(PLAINTEXT.as_bytes(), plaintext.as_slice());
+    }
+}
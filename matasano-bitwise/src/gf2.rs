@@ -0,0 +1,493 @@
+//! Linear algebra over GF(2), the field with two elements where addition is XOR and
+//! multiplication is AND.
+//!
+//! Vectors and matrix rows are packed in `u64` words, so row operations are performed 64 bits at
+//! a time. Bit `i` of a [`BitVec`] lives in word `i / 64`, at position `i % 64`.
+
+use std::{
+    fmt,
+    ops::{BitXor, BitXorAssign, Mul},
+};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+#[inline]
+fn words_for(len: usize) -> usize {
+    len.div_ceil(WORD_BITS)
+}
+
+/// A fixed length vector over GF(2).
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// Creates a vector with `len` bits, all of them set to zero.
+    pub fn new(len: usize) -> Self {
+        BitVec {
+            words: vec![0; words_for(len)],
+            len,
+        }
+    }
+
+    /// Creates a vector from the `len` least significant bits of `value`. Bit `i` of the vector
+    /// is bit `i` of `value`.
+    pub fn from_u64(value: u64, len: usize) -> Self {
+        assert!(len <= WORD_BITS, "a u64 only has 64 bits");
+        let mut res = BitVec::new(len);
+        if len > 0 {
+            res.words[0] = value & (u64::MAX >> (WORD_BITS - len));
+        }
+        res
+    }
+
+    /// Creates a vector from a sequence of bytes, using the same big-endian bit numbering as
+    /// [`crate::bits`]: bit 0 is the most significant bit of the first byte.
+    pub fn from_bytes(bytes: impl AsRef<[u8]>) -> Self {
+        let bytes = bytes.as_ref();
+        let mut res = BitVec::new(bytes.len() * 8);
+        for i in 0..res.len {
+            if crate::bits::get_bit(bytes, i) {
+                res.set(i, true);
+            }
+        }
+        res
+    }
+
+    /// Inverse of [`BitVec::from_u64`]. Panics if the vector is longer than 64 bits.
+    pub fn to_u64(&self) -> u64 {
+        assert!(self.len <= WORD_BITS, "the vector doesn't fit in a u64");
+        self.words.first().copied().unwrap_or_default()
+    }
+
+    /// Inverse of [`BitVec::from_bytes`]. The last byte is padded with zeros if the length of
+    /// the vector isn't a multiple of 8.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = vec![0; self.len.div_ceil(8)];
+        for i in self.iter_ones() {
+            crate::bits::set_bit(&mut res, i, true);
+        }
+        res
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if every bit is zero.
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit index out of bounds");
+        (self.words[i / WORD_BITS] >> (i % WORD_BITS)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "bit index out of bounds");
+        let mask = 1 << (i % WORD_BITS);
+        if value {
+            self.words[i / WORD_BITS] |= mask;
+        } else {
+            self.words[i / WORD_BITS] &= !mask;
+        }
+    }
+
+    pub fn flip(&mut self, i: usize) {
+        assert!(i < self.len, "bit index out of bounds");
+        self.words[i / WORD_BITS] ^= 1 << (i % WORD_BITS);
+    }
+
+    /// Number of bits set, i.e. the Hamming weight of the vector.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Inner product of two vectors, which is the parity of their bitwise AND.
+    pub fn dot(&self, other: &BitVec) -> bool {
+        assert_eq!(self.len, other.len, "vectors must have the same length");
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones())
+            & 1
+            == 1
+    }
+
+    /// Iterates over the indices of the bits that are set, in increasing order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut w = *word;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    None
+                } else {
+                    let bit = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    Some(i * WORD_BITS + bit)
+                }
+            })
+        })
+    }
+
+    /// XORs `other` into `self`, starting at word `from`. Words before it are left untouched.
+    #[inline]
+    fn xor_from(&mut self, other: &[u64], from: usize) {
+        for (a, b) in self.words[from..].iter_mut().zip(other[from..].iter()) {
+            *a ^= b;
+        }
+    }
+}
+
+impl fmt::Debug for BitVec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bits = (0..self.len)
+            .map(|i| if self.get(i) { '1' } else { '0' })
+            .collect::<String>();
+        write!(f, "BitVec({})", bits)
+    }
+}
+
+impl BitXorAssign<&BitVec> for BitVec {
+    fn bitxor_assign(&mut self, rhs: &BitVec) {
+        assert_eq!(self.len, rhs.len, "vectors must have the same length");
+        self.xor_from(&rhs.words, 0);
+    }
+}
+
+impl BitXor for &BitVec {
+    type Output = BitVec;
+
+    fn bitxor(self, rhs: &BitVec) -> BitVec {
+        let mut res = self.clone();
+        res ^= rhs;
+        res
+    }
+}
+
+/// A dense matrix over GF(2), stored as a list of packed rows.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    rows: Vec<BitVec>,
+    cols: usize,
+}
+
+impl BitMatrix {
+    /// Creates a `rows` x `cols` matrix where every entry is zero.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        BitMatrix {
+            rows: vec![BitVec::new(cols); rows],
+            cols,
+        }
+    }
+
+    /// Creates the `n` x `n` identity matrix.
+    pub fn identity(n: usize) -> Self {
+        let mut res = BitMatrix::new(n, n);
+        for i in 0..n {
+            res.set(i, i, true);
+        }
+        res
+    }
+
+    /// Creates a matrix out of its rows. Panics if they don't all have the same length.
+    pub fn from_rows(rows: Vec<BitVec>) -> Self {
+        let cols = rows.first().map(BitVec::len).unwrap_or_default();
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "rows must have the same length"
+        );
+        BitMatrix { rows, cols }
+    }
+
+    /// Creates a matrix out of its columns. Panics if they don't all have the same length.
+    pub fn from_columns(columns: &[BitVec]) -> Self {
+        let rows = columns.first().map(BitVec::len).unwrap_or_default();
+        let mut res = BitMatrix::new(rows, columns.len());
+        for (j, column) in columns.iter().enumerate() {
+            assert_eq!(rows, column.len(), "columns must have the same length");
+            for i in column.iter_ones() {
+                res.set(i, j, true);
+            }
+        }
+        res
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &BitVec {
+        &self.rows[i]
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        self.rows[i].get(j)
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, value: bool) {
+        self.rows[i].set(j, value)
+    }
+
+    pub fn transpose(&self) -> BitMatrix {
+        let mut res = BitMatrix::new(self.cols, self.rows());
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.iter_ones() {
+                res.set(j, i, true);
+            }
+        }
+        res
+    }
+
+    /// Transforms the matrix in place into its reduced row echelon form, using Gauss-Jordan
+    /// elimination. Returns the pivot columns, where the n-th element is the pivot column of
+    /// row n.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for col in 0..self.cols {
+            let pivot_row = pivots.len();
+            if pivot_row == self.rows() {
+                break;
+            }
+
+            let Some(r) = (pivot_row..self.rows()).find(|r| self.rows[*r].get(col)) else {
+                continue;
+            };
+            self.rows.swap(pivot_row, r);
+
+            // Every bit of the pivot row before `col` is zero, so the words before the one that
+            // holds `col` can be skipped.
+            let from = col / WORD_BITS;
+            let pivot = self.rows[pivot_row].words.clone();
+            for (i, row) in self.rows.iter_mut().enumerate() {
+                if i != pivot_row && row.get(col) {
+                    row.xor_from(&pivot, from);
+                }
+            }
+
+            pivots.push(col);
+        }
+
+        pivots
+    }
+
+    /// Number of linearly independent rows (or columns).
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// Returns a basis of the kernel (null space) of the matrix, i.e. of every `x` such that
+    /// `Ax = 0`. The basis is empty if the matrix has full column rank.
+    pub fn kernel(&self) -> Vec<BitVec> {
+        let mut rref = self.clone();
+        let pivots = rref.row_reduce();
+        let mut is_pivot = vec![false; self.cols];
+        for col in &pivots {
+            is_pivot[*col] = true;
+        }
+
+        (0..self.cols)
+            .filter(|col| !is_pivot[*col])
+            .map(|free| {
+                let mut v = BitVec::new(self.cols);
+                v.set(free, true);
+                for (r, col) in pivots.iter().enumerate() {
+                    if rref.get(r, free) {
+                        v.set(*col, true);
+                    }
+                }
+                v
+            })
+            .collect()
+    }
+
+    /// Finds one solution of `Ax = b`, or returns `None` if the system is inconsistent. Free
+    /// variables are set to zero; every other solution can be obtained by adding a linear
+    /// combination of the vectors returned by [`BitMatrix::kernel`].
+    pub fn solve(&self, b: &BitVec) -> Option<BitVec> {
+        assert_eq!(self.rows(), b.len(), "b must have one bit per row");
+
+        let mut augmented = BitMatrix::new(self.rows(), self.cols + 1);
+        for (i, row) in self.rows.iter().enumerate() {
+            for j in row.iter_ones() {
+                augmented.set(i, j, true);
+            }
+            augmented.set(i, self.cols, b.get(i));
+        }
+
+        let pivots = augmented.row_reduce();
+        if pivots.last() == Some(&self.cols) {
+            return None;
+        }
+
+        let mut x = BitVec::new(self.cols);
+        for (r, col) in pivots.iter().enumerate() {
+            x.set(*col, augmented.get(r, self.cols));
+        }
+
+        Some(x)
+    }
+}
+
+impl fmt::Debug for BitMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows.iter()).finish()
+    }
+}
+
+impl Mul<&BitVec> for &BitMatrix {
+    type Output = BitVec;
+
+    fn mul(self, rhs: &BitVec) -> BitVec {
+        assert_eq!(self.cols, rhs.len(), "dimension mismatch");
+        let mut res = BitVec::new(self.rows());
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.dot(rhs) {
+                res.set(i, true);
+            }
+        }
+        res
+    }
+}
+
+impl Mul for &BitMatrix {
+    type Output = BitMatrix;
+
+    fn mul(self, rhs: &BitMatrix) -> BitMatrix {
+        assert_eq!(self.cols, rhs.rows(), "dimension mismatch");
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter_ones().fold(BitVec::new(rhs.cols), |mut acc, j| {
+                    acc ^= &rhs.rows[j];
+                    acc
+                })
+            })
+            .collect();
+
+        BitMatrix {
+            rows,
+            cols: rhs.cols,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Small linear congruential generator so that the tests don't depend on a source of
+    /// randomness.
+    fn lcg(state: &mut u64) -> u64 {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *state
+    }
+
+    fn random_matrix(rows: usize, cols: usize, state: &mut u64) -> BitMatrix {
+        let mut res = BitMatrix::new(rows, cols);
+        for i in 0..rows {
+            for j in 0..cols {
+                res.set(i, j, lcg(state) >> 63 == 1);
+            }
+        }
+        res
+    }
+
+    fn temper(mut y: u32) -> u32 {
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9D2C5680;
+        y ^= (y << 15) & 0xEFC60000;
+        y ^ (y >> 18)
+    }
+
+    #[test]
+    fn test_bitvec() {
+        let mut v = BitVec::from_bytes([0x80, 0x01]);
+        assert!(v.get(0));
+        assert!(v.get(15));
+        assert_eq!(vec![0, 15], v.iter_ones().collect::<Vec<_>>());
+
+        v.flip(15);
+        v.set(6, true);
+        assert_eq!(vec![0x82, 0x00], v.to_bytes());
+        assert_eq!(0b1011, BitVec::from_u64(0xFB, 4).to_u64());
+        assert!(BitVec::from_bytes([0x81]).dot(&BitVec::from_bytes([0x01])));
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(130, BitMatrix::identity(130).rank());
+
+        let mut m = BitMatrix::new(3, 3);
+        m.set(0, 0, true);
+        m.set(1, 1, true);
+        m.set(2, 0, true);
+        m.set(2, 1, true);
+        assert_eq!(2, m.rank());
+    }
+
+    #[test]
+    fn test_mul() {
+        let mut state = 1;
+        let a = random_matrix(40, 70, &mut state);
+        let b = random_matrix(70, 90, &mut state);
+        let x = random_matrix(1, 90, &mut state).row(0).clone();
+
+        assert_eq!(&(&a * &b) * &x, &a * &(&b * &x));
+        assert_eq!(a, &a * &BitMatrix::identity(70));
+        assert_eq!(&b.transpose() * &a.transpose(), (&a * &b).transpose());
+    }
+
+    #[test]
+    fn test_solve_and_kernel() {
+        let mut state = 7;
+        let a = random_matrix(128, 200, &mut state);
+        let x = random_matrix(1, 200, &mut state).row(0).clone();
+        let b = &a * &x;
+
+        let solution = a.solve(&b).unwrap();
+        assert_eq!(b, &a * &solution);
+
+        let kernel = a.kernel();
+        assert_eq!(200 - a.rank(), kernel.len());
+        for v in &kernel {
+            assert!((&a * v).is_zero());
+        }
+    }
+
+    #[test]
+    fn test_solve_inconsistent() {
+        let mut m = BitMatrix::new(2, 2);
+        m.set(0, 0, true);
+        m.set(1, 0, true);
+        assert_eq!(None, m.solve(&BitVec::from_u64(0b10, 2)));
+    }
+
+    #[test]
+    fn test_untemper() {
+        // MT19937's tempering is linear over GF(2), so it can be inverted by solving a system.
+        let columns = (0..32)
+            .map(|j| BitVec::from_u64(u64::from(temper(1 << j)), 32))
+            .collect::<Vec<_>>();
+        let t = BitMatrix::from_columns(&columns);
+
+        let y = 0xDEADBEEF_u32;
+        let tempered = BitVec::from_u64(u64::from(temper(y)), 32);
+        assert_eq!(u64::from(y), t.solve(&tempered).unwrap().to_u64());
+    }
+}
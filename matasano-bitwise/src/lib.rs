@@ -2,16 +2,24 @@
 
 use std::ops::{BitAnd, BitOr, BitXor};
 
-use matasano_util::hex_str_to_bytes;
+use matasano_util::{hex_str_to_bytes, Error};
 use paste::paste;
 
+pub mod bits;
+pub mod break_xor;
+pub mod gf2;
+pub mod magic;
+pub mod period;
+pub mod randomness;
+pub mod stream;
+
 macro_rules! boolean_ops {
     ( $( $name:ident => $op:ident ),* ) => {$(
         paste! {
-            pub fn [<$name _str>](a: &str, b: &str) -> Vec<u8> {
-                let a = hex_str_to_bytes(a);
-                let b = hex_str_to_bytes(b);
-                $name(a, b)
+            pub fn [<$name _str>](a: &str, b: &str) -> Result<Vec<u8>, Error> {
+                let a = hex_str_to_bytes(a)?;
+                let b = hex_str_to_bytes(b)?;
+                Ok($name(a, b))
             }
 
             pub fn $name(a: impl AsRef<[u8]>, b: impl AsRef<[u8]>) -> Vec<u8> {
@@ -79,10 +87,7 @@ pub fn count_bits_set(mut v: u8) -> usize {
 
 /// Calculates the Hamming distance between two sequences of bytes.
 pub fn hamming_distance(a: impl AsRef<[u8]>, b: impl AsRef<[u8]>) -> usize {
-    xor(a, b)
-        .iter()
-        .map(|byte| count_bits_set(*byte))
-        .sum::<usize>()
+    bits::count_ones(xor(a, b))
 }
 
 #[cfg(test)]
@@ -100,9 +105,16 @@ mod tests {
                 "1c0111001f010100061a024b53535009181c",
                 "686974207468652062756c6c277320657965"
             )
+            .unwrap()
         );
     }
 
+    #[test]
+    fn test_str_errors() {
+        assert!(xor_str("1c01", "zz").is_err());
+        assert!(and_str("1c0", "68").is_err());
+    }
+
     #[test]
     fn test_xor_cycle() {
         let a = "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
@@ -111,7 +123,8 @@ mod tests {
         assert_eq!(
             hex_str_to_bytes(
                 "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"
-            ),
+            )
+            .unwrap(),
             xor_cycle(a, b)
         );
     }
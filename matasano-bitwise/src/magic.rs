@@ -0,0 +1,310 @@
+//! Automatic detection and decoding of layered encodings and ciphers, in the spirit of
+//! CyberChef's Magic operation.
+//!
+//! Starting from the input, [`Magic`] applies every operation that makes sense at each step and
+//! recurses on the outputs, up to a maximum depth. Every chain of operations it tries becomes a
+//! [`Recipe`], and recipes are ranked by the score of the bytes they produce.
+//!
+//! ```
+//! use matasano_bitwise::magic::{Magic, Operation};
+//!
+//! let recipe = Magic::default()
+//!     .best("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736");
+//! assert_eq!(
+//!     vec![Operation::FromHex, Operation::SingleByteXor(88)],
+//!     recipe.operations
+//! );
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use matasano_constants::score::{Scorer, Weighted};
+
+use crate::break_xor;
+
+/// Maximum number of operations in a recipe, unless configured otherwise.
+const DEFAULT_MAX_DEPTH: usize = 3;
+
+/// Block size used to look for repeated ECB blocks.
+const ECB_BLOCK_SIZE: usize = 16;
+
+/// Minimum number of bytes per key byte for a repeating-key XOR break to be trusted. With fewer,
+/// every column is so short that a long key can fit almost anything.
+const MIN_COLUMN_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    FromHex,
+    FromBase64,
+    SingleByteXor(u8),
+    RepeatingKeyXor(Vec<u8>),
+    /// Doesn't change its input, but marks it as ECB ciphertext, with the number of repeated
+    /// blocks. Nothing else is tried after it.
+    DetectEcb(usize),
+}
+
+impl Operation {
+    fn is_xor(&self) -> bool {
+        matches!(
+            self,
+            Operation::SingleByteXor(_) | Operation::RepeatingKeyXor(_)
+        )
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::FromHex => write!(f, "from hex"),
+            Operation::FromBase64 => write!(f, "from Base64"),
+            Operation::SingleByteXor(key) => write!(f, "XOR with {:#04x}", key),
+            Operation::RepeatingKeyXor(key) => {
+                write!(f, "XOR with key {:?}", String::from_utf8_lossy(key))
+            }
+            Operation::DetectEcb(repeats) => write!(f, "ECB ({} repeated blocks)", repeats),
+        }
+    }
+}
+
+/// A chain of operations, the bytes it produced from the input and their score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recipe {
+    pub operations: Vec<Operation>,
+    pub output: Vec<u8>,
+    pub score: f64,
+}
+
+/// The operations separated by arrows, or `identity` for the empty recipe.
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.operations.is_empty() {
+            return write!(f, "identity");
+        }
+
+        for (i, op) in self.operations.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{}", op)?;
+        }
+        Ok(())
+    }
+}
+
+/// `bytes` without ASCII whitespace, so that wrapped encodings can be decoded.
+fn strip_whitespace(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .filter(|b| !b.is_ascii_whitespace())
+        .copied()
+        .collect()
+}
+
+fn decode_hex(bytes: &[u8]) -> Option<Vec<u8>> {
+    let bytes = strip_whitespace(bytes);
+    if bytes.is_empty() {
+        return None;
+    }
+
+    matasano_util::hex_str_to_bytes(std::str::from_utf8(&bytes).ok()?).ok()
+}
+
+fn decode_base64(bytes: &[u8]) -> Option<Vec<u8>> {
+    let bytes = strip_whitespace(bytes);
+    if bytes.is_empty() {
+        return None;
+    }
+
+    matasano_encoding::base64::decode(std::str::from_utf8(&bytes).ok()?).ok()
+}
+
+/// Number of `ECB_BLOCK_SIZE` blocks of `bytes` that are equal to an earlier one.
+fn repeated_blocks(bytes: &[u8]) -> usize {
+    let blocks = bytes.chunks_exact(ECB_BLOCK_SIZE);
+    let total = blocks.len();
+    total - blocks.collect::<HashSet<_>>().len()
+}
+
+/// Search engine over chains of decodings and XOR breaks.
+pub struct Magic<S> {
+    scorer: S,
+    max_depth: usize,
+}
+
+impl<S: Scorer + Sync> Magic<S> {
+    /// Ranks recipes with `scorer`, which should give higher scores to likely plaintexts.
+    pub fn new(scorer: S) -> Self {
+        Magic {
+            scorer,
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Sets the maximum number of operations in a recipe.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Every recipe tried on `input`, including the empty one, sorted by score with the best one
+    /// first.
+    pub fn run(&self, input: impl AsRef<[u8]>) -> Vec<Recipe> {
+        let mut res = Vec::new();
+        self.explore(Vec::new(), input.as_ref().to_vec(), &mut res);
+
+        res.sort_by(|a, b| b.score.total_cmp(&a.score));
+        res
+    }
+
+    /// The best recipe for `input`. It's the empty recipe if nothing improves on the input as it
+    /// is.
+    pub fn best(&self, input: impl AsRef<[u8]>) -> Recipe {
+        self.run(input).swap_remove(0)
+    }
+
+    /// Operations that apply to `bytes`, the output of `operations`, with their outputs.
+    fn next_steps(&self, operations: &[Operation], bytes: &[u8]) -> Vec<(Operation, Vec<u8>)> {
+        let mut res = Vec::new();
+
+        if let Some(decoded) = decode_hex(bytes) {
+            res.push((Operation::FromHex, decoded));
+        }
+        if let Some(decoded) = decode_base64(bytes) {
+            res.push((Operation::FromBase64, decoded));
+        }
+        // Encoded ciphertext has to be decoded first, and its encoding repeats as much as it does.
+        if !res.is_empty() {
+            return res;
+        }
+
+        let repeats = repeated_blocks(bytes);
+        if repeats > 0 && bytes.len().is_multiple_of(ECB_BLOCK_SIZE) {
+            // ECB ciphertext can't be decoded any further without the key.
+            res.push((Operation::DetectEcb(repeats), bytes.to_vec()));
+            return res;
+        }
+
+        // XORing twice is the same as XORing once with a combined key.
+        if bytes.is_empty() || operations.last().is_some_and(Operation::is_xor) {
+            return res;
+        }
+
+        let (key, plaintext, _) = break_xor::rank_single_byte_xor(bytes, &self.scorer, 1).remove(0);
+        if key != 0 {
+            res.push((Operation::SingleByteXor(key), plaintext));
+        }
+
+        if bytes.len() >= 2 * MIN_COLUMN_LEN {
+            if let Some((key, plaintext, _)) =
+                break_xor::break_repeating_key_xor(bytes, &self.scorer)
+                    .into_iter()
+                    .filter(|(key, _, _)| bytes.len() / key.len() >= MIN_COLUMN_LEN)
+                    .find(|(key, _, _)| key.iter().any(|b| *b != key[0]))
+            {
+                res.push((Operation::RepeatingKeyXor(key), plaintext));
+            }
+        }
+
+        res
+    }
+
+    fn explore(&self, operations: Vec<Operation>, bytes: Vec<u8>, res: &mut Vec<Recipe>) {
+        let steps = if operations.len() < self.max_depth
+            && !matches!(operations.last(), Some(Operation::DetectEcb(_)))
+        {
+            self.next_steps(&operations, &bytes)
+        } else {
+            Vec::new()
+        };
+
+        for (op, output) in steps {
+            let mut operations = operations.clone();
+            operations.push(op);
+            self.explore(operations, output, res);
+        }
+
+        res.push(Recipe {
+            score: self.scorer.score(&bytes),
+            operations,
+            output: bytes,
+        });
+    }
+}
+
+/// Ranks recipes against English plaintexts.
+impl Default for Magic<Weighted> {
+    fn default() -> Self {
+        Magic::new(Weighted::english())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoders() {
+        assert_eq!(Some(vec![0xde, 0xad]), decode_hex(b"de ad\n"));
+        assert_eq!(None, decode_hex(b"dea"));
+        assert_eq!(None, decode_hex(b"xyzw"));
+        assert_eq!(Some(b"any".to_vec()), decode_base64(b"YW55"));
+        assert_eq!(Some(b"an".to_vec()), decode_base64(b"YW4=\n"));
+        assert_eq!(None, decode_base64(b"YW=4"));
+        assert_eq!(None, decode_base64(b"YW5"));
+    }
+
+    #[test]
+    fn test_single_byte_xor() {
+        let recipe = Magic::default()
+            .best("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736");
+
+        assert_eq!(
+            vec![Operation::FromHex, Operation::SingleByteXor(88)],
+            recipe.operations
+        );
+        assert_eq!(
+            b"Cooking MC's like a pound of bacon".to_vec(),
+            recipe.output
+        );
+        assert_eq!("from hex -> XOR with 0x58", recipe.to_string());
+    }
+
+    #[test]
+    fn test_repeating_key_xor() {
+        let input = include_bytes!("../../matasano-challenges/data/6.txt");
+        let recipe = Magic::default().best(input);
+
+        assert_eq!(
+            vec![
+                Operation::FromBase64,
+                Operation::RepeatingKeyXor(b"Terminator X: Bring the noise".to_vec())
+            ],
+            recipe.operations
+        );
+        assert!(recipe
+            .output
+            .starts_with(b"I'm back and I'm ringin' the bell"));
+    }
+
+    #[test]
+    fn test_detect_ecb() {
+        let mut ciphertext = (0..32).collect::<Vec<u8>>();
+        ciphertext.extend_from_within(..16);
+        let input = ciphertext
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+
+        let recipes = Magic::default().run(input);
+        assert!(recipes
+            .iter()
+            .any(|r| r.operations == [Operation::FromHex, Operation::DetectEcb(1)]));
+    }
+
+    #[test]
+    fn test_plaintext_is_left_alone() {
+        let recipe = Magic::default().best("Now that the party is jumping");
+        assert!(recipe.operations.is_empty());
+        assert_eq!("identity", recipe.to_string());
+    }
+}
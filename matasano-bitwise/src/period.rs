@@ -0,0 +1,270 @@
+//! Statistical tests to find the period of a repeating-key cipher, such as repeating-key XOR or
+//! Vigenère.
+//!
+//! Every `rank_by_*` function takes the candidate key sizes to try and returns them as
+//! `(keysize, statistic)` tuples, with the most likely key size first. The meaning of the
+//! statistic depends on the test. Since the tests are independent, combining their best
+//! candidates is more reliable than trusting any of them alone.
+
+use std::collections::HashMap;
+
+use crate::hamming_distance;
+
+/// Index of coincidence of a uniformly random byte sequence.
+pub const RANDOM_IOC: f64 = 1.0 / 256.0;
+
+/// Ranks key sizes by the Hamming distance between consecutive blocks of `bytes`, normalised by
+/// the key size and averaged. Lower distances come first. Key sizes for which `bytes` doesn't
+/// have at least two blocks are skipped.
+pub fn rank_by_hamming(
+    bytes: &[u8],
+    keysizes: impl IntoIterator<Item = usize>,
+) -> Vec<(usize, f64)> {
+    let mut res = Vec::new();
+
+    for keysize in keysizes {
+        if keysize == 0 {
+            continue;
+        }
+
+        let blocks = bytes.chunks_exact(keysize).collect::<Vec<_>>();
+        if blocks.len() < 2 {
+            continue;
+        }
+
+        let distances = blocks
+            .windows(2)
+            .map(|w| hamming_distance(w[0], w[1]) as f64 / keysize as f64)
+            .collect::<Vec<_>>();
+
+        res.push((
+            keysize,
+            distances.iter().sum::<f64>() / distances.len() as f64,
+        ));
+    }
+
+    res.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    res
+}
+
+/// Probability that two bytes picked at random from `bytes`, without replacement, are equal.
+/// English text is around 0.06, while random bytes are close to [`RANDOM_IOC`]. Zero if `bytes`
+/// has fewer than two bytes.
+///
+/// Applying a substitution to every byte, such as XORing them all with the same key, doesn't
+/// change the index.
+pub fn index_of_coincidence(bytes: &[u8]) -> f64 {
+    let n = bytes.len();
+    if n < 2 {
+        return 0.0;
+    }
+
+    let mut counts = [0_usize; 256];
+    for byte in bytes {
+        counts[*byte as usize] += 1;
+    }
+
+    let coincidences = counts
+        .iter()
+        .map(|c| c * c.saturating_sub(1))
+        .sum::<usize>();
+    coincidences as f64 / (n * (n - 1)) as f64
+}
+
+/// Index of coincidence of each of the `keysize` columns of `bytes`, where the n-th column is
+/// made of every byte whose position modulo `keysize` is n. Empty if `keysize` is 0.
+pub fn column_index_of_coincidence(bytes: &[u8], keysize: usize) -> Vec<f64> {
+    if keysize == 0 {
+        return Vec::new();
+    }
+
+    let mut columns = vec![Vec::new(); keysize];
+    for (i, byte) in bytes.iter().enumerate() {
+        columns[i % keysize].push(*byte);
+    }
+
+    columns
+        .iter()
+        .map(|column| index_of_coincidence(column))
+        .collect()
+}
+
+/// Average of [`column_index_of_coincidence`]. When `keysize` is a multiple of the period, every
+/// column was encrypted with a single key byte and the average is close to the index of the
+/// plaintext. Returns 0 if `keysize` is 0.
+pub fn average_index_of_coincidence(bytes: &[u8], keysize: usize) -> f64 {
+    let columns = column_index_of_coincidence(bytes, keysize);
+    if columns.is_empty() {
+        return 0.0;
+    }
+
+    columns.iter().sum::<f64>() / columns.len() as f64
+}
+
+/// Ranks key sizes by [`average_index_of_coincidence`], higher first. Multiples of the period
+/// score about as well as the period itself (or even better, since their columns are shorter and
+/// noisier), so key sizes within 20% of the best are ranked from the smallest. Key sizes that would
+/// leave a column with fewer than two bytes are skipped.
+pub fn rank_by_index_of_coincidence(
+    bytes: &[u8],
+    keysizes: impl IntoIterator<Item = usize>,
+) -> Vec<(usize, f64)> {
+    let mut res = keysizes
+        .into_iter()
+        .filter(|keysize| *keysize > 0 && bytes.len() >= 2 * keysize)
+        .map(|keysize| (keysize, average_index_of_coincidence(bytes, keysize)))
+        .collect::<Vec<_>>();
+
+    res.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let best = res.first().map(|(_, ioc)| *ioc).unwrap_or_default();
+    let near_best = res.iter().take_while(|(_, ioc)| *ioc >= 0.8 * best).count();
+    res[..near_best].sort_by_key(|(keysize, _)| *keysize);
+
+    res
+}
+
+/// Distances between consecutive occurrences of every substring of `len` bytes that appears more
+/// than once in `bytes`.
+pub fn kasiski_distances(bytes: &[u8], len: usize) -> Vec<usize> {
+    if len == 0 {
+        return Vec::new();
+    }
+
+    let mut last_seen = HashMap::<&[u8], usize>::new();
+    let mut res = Vec::new();
+
+    for (i, window) in bytes.windows(len).enumerate() {
+        if let Some(prev) = last_seen.insert(window, i) {
+            res.push(i - prev);
+        }
+    }
+
+    res
+}
+
+/// Kasiski examination: repeated substrings of at least `len` bytes were most likely encrypted
+/// with the same part of the key, so the distances between them are multiples of the period.
+///
+/// Every key size is scored with the fraction of distances it divides, minus the fraction `1 /
+/// keysize` that random distances would give, and higher scores come first. Returns an empty Vec
+/// if no substring is repeated.
+pub fn rank_by_kasiski(
+    bytes: &[u8],
+    len: usize,
+    keysizes: impl IntoIterator<Item = usize>,
+) -> Vec<(usize, f64)> {
+    let distances = kasiski_distances(bytes, len);
+    if distances.is_empty() {
+        return Vec::new();
+    }
+
+    let mut res = keysizes
+        .into_iter()
+        .filter(|keysize| *keysize > 0)
+        .map(|keysize| {
+            let divided = distances.iter().filter(|d| *d % keysize == 0).count();
+            let fraction = divided as f64 / distances.len() as f64;
+            (keysize, fraction - 1.0 / keysize as f64)
+        })
+        .collect::<Vec<_>>();
+
+    res.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    res
+}
+
+/// Friedman test: estimates the period from the index of coincidence of the whole of `bytes`,
+/// given the index of coincidence of the plaintext language (see [`index_of_coincidence`]). The
+/// more key bytes, the closer the ciphertext gets to [`RANDOM_IOC`].
+///
+/// The estimate is rough and only meaningful for long ciphertexts. Returns infinity if the
+/// ciphertext looks random.
+pub fn friedman(bytes: &[u8], plaintext_ioc: f64) -> f64 {
+    let n = bytes.len() as f64;
+    let observed = index_of_coincidence(bytes);
+
+    let numerator = (plaintext_ioc - RANDOM_IOC) * n;
+    let denominator = (n - 1.0) * observed - RANDOM_IOC * n + plaintext_ioc;
+
+    if denominator <= 0.0 {
+        f64::INFINITY
+    } else {
+        numerator / denominator
+    }
+}
+
+/// Ranks key sizes by their distance to the [`friedman`] estimate, closest first.
+pub fn rank_by_friedman(
+    bytes: &[u8],
+    plaintext_ioc: f64,
+    keysizes: impl IntoIterator<Item = usize>,
+) -> Vec<(usize, f64)> {
+    let estimate = friedman(bytes, plaintext_ioc);
+    let mut res = keysizes
+        .into_iter()
+        .map(|keysize| (keysize, (keysize as f64 - estimate).abs()))
+        .collect::<Vec<_>>();
+
+    res.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xor_cycle;
+
+    const PLAINTEXT: &str = include_str!("../../testdata/english.txt");
+
+    fn ciphertext() -> Vec<u8> {
+        xor_cycle(PLAINTEXT, "Harbour")
+    }
+
+    #[test]
+    fn test_index_of_coincidence() {
+        assert_eq!(0.0, index_of_coincidence(b"a"));
+        assert_eq!(1.0, index_of_coincidence(b"aaaa"));
+        assert_eq!(1.0 / 3.0, index_of_coincidence(b"aabb"));
+        assert_eq!(
+            index_of_coincidence(PLAINTEXT.as_bytes()),
+            index_of_coincidence(&xor_cycle(PLAINTEXT, [42]))
+        );
+    }
+
+    #[test]
+    fn test_column_index_of_coincidence() {
+        assert_eq!(vec![1.0, 0.0], column_index_of_coincidence(b"abac", 2));
+        assert_eq!(0.5, average_index_of_coincidence(b"abac", 2));
+        assert!(column_index_of_coincidence(b"abac", 0).is_empty());
+        assert_eq!(0.0, average_index_of_coincidence(b"abac", 0));
+    }
+
+    #[test]
+    fn test_rank_by_hamming() {
+        let ranking = rank_by_hamming(&ciphertext(), 2..=20);
+        assert!(ranking.iter().take(3).any(|(keysize, _)| *keysize == 7));
+    }
+
+    #[test]
+    fn test_rank_by_index_of_coincidence() {
+        assert_eq!(7, rank_by_index_of_coincidence(&ciphertext(), 2..=40)[0].0);
+    }
+
+    #[test]
+    fn test_kasiski() {
+        assert_eq!(vec![3, 3, 3, 3], kasiski_distances(b"abcabcabc", 3));
+        assert_eq!(7, rank_by_kasiski(&ciphertext(), 3, 2..=40)[0].0);
+        assert!(rank_by_kasiski(b"abcdef", 3, 2..=40).is_empty());
+    }
+
+    #[test]
+    fn test_friedman() {
+        let plaintext_ioc = index_of_coincidence(PLAINTEXT.as_bytes());
+        let estimate = friedman(&ciphertext(), plaintext_ioc);
+        assert!((3.0..=12.0).contains(&estimate), "{}", estimate);
+        assert_eq!(
+            estimate.round() as usize,
+            rank_by_friedman(&ciphertext(), plaintext_ioc, 2..=40)[0].0
+        );
+    }
+}
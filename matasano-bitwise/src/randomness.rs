@@ -0,0 +1,569 @@
+//! Statistical tests that tell random-looking bytes apart from everything else, to get an idea of
+//! what an unknown blob is: ciphertext from a good cipher, compressed data, encoded text or
+//! plaintext XORed with a key.
+//!
+//! Every test returns a [`TestResult`] with its statistic and a p-value: the probability that a
+//! truly random sequence gives a result at least as extreme. A p-value below the significance
+//! level, usually [`ALPHA`], means the sequence doesn't look random. The bit-level tests follow
+//! NIST SP 800-22, reading bytes most significant bit first.
+//!
+//! Byte-level tests compare the counts of the 256 byte values with their expected counts, so they
+//! need a few kilobytes to be meaningful.
+
+use std::collections::HashMap;
+
+use crate::count_bits_set;
+
+/// Significance level recommended by NIST SP 800-22.
+pub const ALPHA: f64 = 0.01;
+
+/// Window and maximum match length of the LZ77 parse used by [`compression`].
+const WINDOW: usize = 1 << 15;
+const MAX_MATCH: usize = 258;
+
+/// How many earlier occurrences of a 3-byte prefix [`compression`] tries before giving up.
+const MAX_CANDIDATES: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    fn new(statistic: f64, p_value: f64) -> Self {
+        TestResult {
+            statistic,
+            p_value: p_value.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Whether the sequence looks random at significance level `alpha`.
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.p_value >= alpha
+    }
+}
+
+/// Complementary error function, with a fractional error below 1.2e-7 (Numerical Recipes,
+/// `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let res = t * poly.exp();
+
+    if x >= 0.0 {
+        res
+    } else {
+        2.0 - res
+    }
+}
+
+/// Natural logarithm of the gamma function, for `x > 0` (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized lower incomplete gamma function P(a, x), by its series expansion. Converges
+/// quickly for `x < a + 1`.
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    let mut n = a;
+
+    for _ in 0..1000 {
+        n += 1.0;
+        term *= x / n;
+        sum += term;
+        if term.abs() < sum.abs() * 1e-15 {
+            break;
+        }
+    }
+
+    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), by its continued fraction (modified
+/// Lentz's method). Converges quickly for `x >= a + 1`.
+fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / TINY;
+    let mut d = 1.0 / b;
+    let mut h = d;
+
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = b + an / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+
+    h * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), called `igamc` in NIST SP 800-22. The
+/// p-value of a chi-squared statistic `x` with `k` degrees of freedom is `igamc(k / 2, x / 2)`.
+fn igamc(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        1.0
+    } else if x < a + 1.0 {
+        1.0 - gamma_series(a, x)
+    } else {
+        gamma_continued_fraction(a, x)
+    }
+}
+
+fn byte_counts(bytes: &[u8]) -> [usize; 256] {
+    let mut counts = [0; 256];
+    for b in bytes {
+        counts[*b as usize] += 1;
+    }
+    counts
+}
+
+/// Shannon entropy of `bytes` in bits per byte, between 0 and 8, as the statistic. The p-value
+/// comes from the G-test against the uniform distribution, whose statistic is
+/// `2 * n * ln(2) * (8 - entropy)`, with 255 degrees of freedom.
+pub fn entropy(bytes: &[u8]) -> TestResult {
+    let n = bytes.len() as f64;
+    if bytes.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let entropy = -byte_counts(bytes)
+        .iter()
+        .filter(|c| **c > 0)
+        .map(|c| {
+            let p = *c as f64 / n;
+            p * p.log2()
+        })
+        .sum::<f64>();
+    let g = 2.0 * n * std::f64::consts::LN_2 * (8.0 - entropy);
+
+    TestResult::new(entropy, igamc(255.0 / 2.0, g / 2.0))
+}
+
+/// Pearson's chi-squared test of the byte counts against the uniform distribution, with 255
+/// degrees of freedom.
+pub fn chi_squared(bytes: &[u8]) -> TestResult {
+    if bytes.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let expected = bytes.len() as f64 / 256.0;
+    let statistic = byte_counts(bytes)
+        .iter()
+        .map(|c| (*c as f64 - expected).powi(2) / expected)
+        .sum::<f64>();
+
+    TestResult::new(statistic, igamc(255.0 / 2.0, statistic / 2.0))
+}
+
+/// NIST frequency (monobit) test: whether there are as many zeros as ones. The statistic is
+/// `|ones - zeros| / sqrt(n)`, for `n` bits.
+pub fn monobit(bytes: &[u8]) -> TestResult {
+    let n = (bytes.len() * 8) as f64;
+    if bytes.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let ones = bytes.iter().map(|b| count_bits_set(*b)).sum::<usize>() as f64;
+    let statistic = (2.0 * ones - n).abs() / n.sqrt();
+
+    TestResult::new(statistic, erfc(statistic / std::f64::consts::SQRT_2))
+}
+
+/// NIST runs test: whether the number of runs of identical bits, which is the statistic, is what
+/// the proportion of ones predicts. Fails without computing the runs if the proportion of ones is
+/// already too far from one half, as the monobit test would.
+pub fn runs(bytes: &[u8]) -> TestResult {
+    let n = (bytes.len() * 8) as f64;
+    if bytes.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let pi = bytes.iter().map(|b| count_bits_set(*b)).sum::<usize>() as f64 / n;
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    // Every change between two consecutive bits starts a new run: the ones within a byte are the
+    // bits set in the byte XORed with itself shifted by one, and the others are at the boundaries
+    // between bytes.
+    let within = bytes
+        .iter()
+        .map(|b| count_bits_set((b ^ (b >> 1)) & 0x7F))
+        .sum::<usize>();
+    let across = bytes
+        .windows(2)
+        .filter(|w| (w[0] & 1) != (w[1] >> 7))
+        .count();
+    let statistic = (1 + within + across) as f64;
+
+    let p = erfc(
+        (statistic - 2.0 * n * pi * (1.0 - pi)).abs() / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)),
+    );
+    TestResult::new(statistic, p)
+}
+
+/// NIST frequency test within a block: whether the proportion of ones is close to one half in
+/// every block of `block_len` bytes. Leftover bytes are discarded. The statistic is chi-squared
+/// with as many degrees of freedom as there are blocks.
+///
+/// NIST recommends blocks of at least 20 bits and fewer than 100 blocks.
+pub fn block_frequency(bytes: &[u8], block_len: usize) -> TestResult {
+    let blocks = bytes.chunks_exact(block_len.max(1)).collect::<Vec<_>>();
+    if blocks.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let m = (block_len * 8) as f64;
+    let statistic = 4.0
+        * m
+        * blocks
+            .iter()
+            .map(|block| {
+                let ones = block.iter().map(|b| count_bits_set(*b)).sum::<usize>() as f64;
+                (ones / m - 0.5).powi(2)
+            })
+            .sum::<f64>();
+
+    TestResult::new(statistic, igamc(blocks.len() as f64 / 2.0, statistic / 2.0))
+}
+
+/// Correlation coefficient between every byte and the next one, between -1 and 1, as the
+/// statistic. Random bytes are close to 0, while text and images, where a byte predicts the next
+/// one, are well above. The p-value uses the normal approximation, with a standard deviation of
+/// `1 / sqrt(n)`.
+pub fn serial_correlation(bytes: &[u8]) -> TestResult {
+    if bytes.len() < 2 {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let n = (bytes.len() - 1) as f64;
+    let pairs = bytes.windows(2).map(|w| (w[0] as f64, w[1] as f64));
+    let (mut sx, mut sy, mut sxx, mut syy, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        sx += x;
+        sy += y;
+        sxx += x * x;
+        syy += y * y;
+        sxy += x * y;
+    }
+
+    let denominator = ((n * sxx - sx * sx) * (n * syy - sy * sy)).sqrt();
+    if denominator == 0.0 {
+        // Constant sequences are perfectly predictable.
+        return TestResult::new(1.0, 0.0);
+    }
+
+    let statistic = (n * sxy - sx * sy) / denominator;
+    TestResult::new(
+        statistic,
+        erfc((statistic * n.sqrt()).abs() / std::f64::consts::SQRT_2),
+    )
+}
+
+/// Estimated compression ratio of `bytes` as the statistic: the size of a greedy LZ77 encoding,
+/// with 9-bit literals and 24-bit matches of at least 3 bytes, over the original size. Random
+/// bytes don't compress, which gives a ratio of 9/8, while text usually goes below 1/2.
+///
+/// The p-value is the probability of finding at least as many pairs of equal 3-byte sequences as
+/// there are in `bytes`, which is approximately Poisson distributed for random bytes.
+pub fn compression(bytes: &[u8]) -> TestResult {
+    if bytes.is_empty() {
+        return TestResult::new(0.0, 0.0);
+    }
+
+    let mut positions = HashMap::<[u8; 3], Vec<usize>>::new();
+    let mut bits = 0;
+    let mut i = 0;
+
+    let insert = |positions: &mut HashMap<_, Vec<_>>, i: usize| {
+        if let Some(key) = bytes.get(i..i + 3) {
+            positions
+                .entry(key.try_into().unwrap())
+                .or_default()
+                .push(i);
+        }
+    };
+
+    while i < bytes.len() {
+        let longest = bytes
+            .get(i..i + 3)
+            .and_then(|key| positions.get(key))
+            .map(|candidates| {
+                candidates
+                    .iter()
+                    .rev()
+                    .take_while(|j| i - **j <= WINDOW)
+                    .take(MAX_CANDIDATES)
+                    .map(|j| {
+                        bytes[*j..]
+                            .iter()
+                            .zip(&bytes[i..])
+                            .take(MAX_MATCH)
+                            .take_while(|(a, b)| a == b)
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0);
+
+        let len = if longest >= 3 {
+            bits += 24;
+            longest
+        } else {
+            bits += 9;
+            1
+        };
+        for j in i..i + len {
+            insert(&mut positions, j);
+        }
+        i += len;
+    }
+
+    let statistic = bits as f64 / (bytes.len() * 8) as f64;
+
+    let pairs = positions
+        .values()
+        .map(|v| v.len() * (v.len() - 1) / 2)
+        .sum::<usize>();
+    let m = bytes.len().saturating_sub(2) as f64;
+    let expected = m * (m - 1.0) / 2.0 / f64::from(1 << 24);
+    // P(X >= k) for X ~ Poisson(expected) is the regularized lower incomplete gamma P(k, expected).
+    let p = if pairs == 0 {
+        1.0
+    } else {
+        1.0 - igamc(pairs as f64, expected)
+    };
+
+    TestResult::new(statistic, p)
+}
+
+/// Results of every test on the same sequence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Report {
+    pub entropy: TestResult,
+    pub chi_squared: TestResult,
+    pub monobit: TestResult,
+    pub runs: TestResult,
+    pub block_frequency: TestResult,
+    pub serial_correlation: TestResult,
+    pub compression: TestResult,
+}
+
+impl Report {
+    /// Runs every test on `bytes`. The block frequency test uses blocks of 16 bytes, or larger
+    /// ones to keep the number of blocks under 100.
+    pub fn new(bytes: &[u8]) -> Self {
+        Report {
+            entropy: entropy(bytes),
+            chi_squared: chi_squared(bytes),
+            monobit: monobit(bytes),
+            runs: runs(bytes),
+            block_frequency: block_frequency(bytes, (bytes.len() / 99).max(16)),
+            serial_correlation: serial_correlation(bytes),
+            compression: compression(bytes),
+        }
+    }
+
+    pub fn results(&self) -> [(&'static str, TestResult); 7] {
+        [
+            ("entropy", self.entropy),
+            ("chi-squared", self.chi_squared),
+            ("monobit", self.monobit),
+            ("runs", self.runs),
+            ("block frequency", self.block_frequency),
+            ("serial correlation", self.serial_correlation),
+            ("compression", self.compression),
+        ]
+    }
+
+    /// Whether every test passed at significance level `alpha`.
+    pub fn passed(&self, alpha: f64) -> bool {
+        self.results().iter().all(|(_, res)| res.passed(alpha))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Classification {
+    /// Passes every test, like the output of a good cipher.
+    Random,
+    /// Close to 8 bits of entropy per byte, but fails some of the tests.
+    Compressed,
+    /// Only uses the characters of hex or Base64, possibly with whitespace.
+    Encoded,
+    /// Low entropy and not all printable: text or other structured data, XORed with a key.
+    XoredPlaintext,
+    /// Printable text.
+    Plaintext,
+}
+
+/// Best guess of what `bytes` is. This is a heuristic, and it can't say much about short
+/// sequences.
+pub fn classify(bytes: &[u8]) -> Classification {
+    let is_encoding =
+        |c: &u8| c.is_ascii_alphanumeric() || b"+/=".contains(c) || c.is_ascii_whitespace();
+    if !bytes.is_empty() && bytes.iter().all(is_encoding) {
+        let distinct = byte_counts(bytes).iter().filter(|c| **c > 0).count();
+        // Base64 uses 65 characters, and line breaks add a couple more.
+        if distinct <= 68 && !bytes.contains(&b' ') {
+            return Classification::Encoded;
+        }
+    }
+
+    let report = Report::new(bytes);
+    if report.passed(ALPHA) {
+        Classification::Random
+    } else if report.entropy.statistic > 7.5 {
+        Classification::Compressed
+    } else if bytes
+        .iter()
+        .all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace())
+    {
+        Classification::Plaintext
+    } else {
+        Classification::XoredPlaintext
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudorandom bytes (xorshift64*).
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+        (0..len)
+            .map(|_| {
+                state ^= state >> 12;
+                state ^= state << 25;
+                state ^= state >> 27;
+                (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+            })
+            .collect()
+    }
+
+    /// The first `len` bytes of the English corpus, which doesn't repeat itself.
+    fn text(len: usize) -> Vec<u8> {
+        let corpus = include_bytes!("../../matasano-constants/data/corpus/en.txt");
+        assert!(len <= corpus.len(), "the corpus has {} bytes", corpus.len());
+        corpus[..len].to_vec()
+    }
+
+    #[test]
+    fn test_special_functions() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-7);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-7);
+        assert!((ln_gamma(10.0) - 362_880_f64.ln()).abs() < 1e-10);
+        assert!((igamc(1.0, 2.0) - (-2.0_f64).exp()).abs() < 1e-12);
+        // NIST SP 800-22, examples of sections 2.2 and 2.3.
+        assert!((igamc(1.5, 0.5) - 0.801_252).abs() < 1e-6);
+        assert!((erfc(0.632_455_532 / std::f64::consts::SQRT_2) - 0.527_089).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_random_bytes_pass() {
+        let report = Report::new(&random_bytes(100_000));
+
+        assert!(report.passed(ALPHA), "{:?}", report);
+        assert!(report.entropy.statistic > 7.99);
+        assert!((report.compression.statistic - 9.0 / 8.0).abs() < 0.01);
+        assert_eq!(Classification::Random, classify(&random_bytes(100_000)));
+    }
+
+    #[test]
+    fn test_text_fails() {
+        let bytes = text(10_000);
+        let report = Report::new(&bytes);
+
+        assert!(report.results().iter().all(|(_, res)| !res.passed(ALPHA)));
+        assert!(report.entropy.statistic < 5.0);
+        assert!(
+            (0.5..0.8).contains(&report.compression.statistic),
+            "{}",
+            report.compression.statistic
+        );
+        assert_eq!(Classification::Plaintext, classify(&bytes));
+    }
+
+    #[test]
+    fn test_bit_tests() {
+        assert!(!monobit(&[0xFF; 64]).passed(ALPHA));
+        assert_eq!(1.0, monobit(&[0x0F, 0xF0]).p_value);
+
+        // Balanced, but a single run of each.
+        let mut bytes = vec![0xFF; 32];
+        bytes.extend([0; 32]);
+        assert_eq!(2.0, runs(&bytes).statistic);
+        assert!(!runs(&bytes).passed(ALPHA));
+        assert_eq!(512.0, runs(&[0x55; 64]).statistic);
+        assert!(!block_frequency(&bytes, 4).passed(ALPHA));
+    }
+
+    #[test]
+    fn test_classify() {
+        let hex = random_bytes(2_000)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        assert_eq!(Classification::Encoded, classify(hex.as_bytes()));
+
+        let xored = text(10_000)
+            .iter()
+            .zip(b"secret key".iter().cycle())
+            .map(|(a, b)| a ^ b)
+            .collect::<Vec<_>>();
+        assert_eq!(Classification::XoredPlaintext, classify(&xored));
+
+        // The English corpus, compressed with `gzip -9 -n`.
+        let gzip = include_bytes!("../../testdata/en.txt.gz");
+        assert_eq!(Classification::Compressed, classify(gzip));
+    }
+}
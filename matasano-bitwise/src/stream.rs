@@ -0,0 +1,276 @@
+//! Streaming XOR adapters, so that a keystream can be applied to anything that implements
+//! [`Read`] or [`Write`] without holding the whole input in memory.
+
+use std::io::{self, Read, Write};
+
+use crate::xor_mut;
+
+/// Size of the scratch buffer used to generate keystream in [`XorReader`] and [`XorWriter`].
+const CHUNK_SIZE: usize = 512;
+
+/// A source of keystream bytes.
+///
+/// Any iterator of bytes is a keystream, as is a [`RepeatingKey`]. Stream ciphers can implement
+/// this trait directly to be used with [`XorReader`] and [`XorWriter`].
+pub trait Keystream {
+    /// Fills `buf` with the next bytes of keystream. Returns the number of bytes written, which
+    /// is only smaller than `buf.len()` if the keystream has been exhausted.
+    fn fill(&mut self, buf: &mut [u8]) -> usize;
+}
+
+impl<I: Iterator<Item = u8>> Keystream for I {
+    fn fill(&mut self, buf: &mut [u8]) -> usize {
+        let mut n = 0;
+        for (dst, src) in buf.iter_mut().zip(self) {
+            *dst = src;
+            n += 1;
+        }
+        n
+    }
+}
+
+/// Keystream that repeats a key forever, as in [`crate::xor_cycle`]. An empty key is an empty
+/// keystream.
+#[derive(Debug, Clone)]
+pub struct RepeatingKey<T> {
+    key: T,
+    pos: usize,
+}
+
+impl<T: AsRef<[u8]>> RepeatingKey<T> {
+    pub fn new(key: T) -> Self {
+        RepeatingKey { key, pos: 0 }
+    }
+}
+
+impl<T: AsRef<[u8]>> Keystream for RepeatingKey<T> {
+    fn fill(&mut self, buf: &mut [u8]) -> usize {
+        let key = self.key.as_ref();
+        if key.is_empty() {
+            return 0;
+        }
+
+        for dst in buf.iter_mut() {
+            *dst = key[self.pos];
+            self.pos = (self.pos + 1) % key.len();
+        }
+        buf.len()
+    }
+}
+
+/// XORs `buf` in place against the next `buf.len()` bytes of `keystream`.
+fn apply(keystream: &mut impl Keystream, buf: &mut [u8]) -> io::Result<()> {
+    let mut ks = [0; CHUNK_SIZE];
+
+    for chunk in buf.chunks_mut(CHUNK_SIZE) {
+        let ks = &mut ks[..chunk.len()];
+        if keystream.fill(ks) < chunk.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "keystream exhausted",
+            ));
+        }
+        xor_mut(chunk, ks);
+    }
+
+    Ok(())
+}
+
+/// Wraps a reader and XORs everything read from it against a keystream.
+///
+/// Each read asks for keystream first and never takes more bytes from the inner reader than it
+/// got, so a short keystream ends a read early instead of losing input. Once the keystream is
+/// exhausted, reads fail with [`io::ErrorKind::UnexpectedEof`], unless the inner reader is at its
+/// end too. Telling them apart takes a byte from the inner reader.
+///
+/// ```
+/// use std::io::Read;
+///
+/// use matasano_bitwise::stream::{RepeatingKey, XorReader};
+///
+/// let mut reader = XorReader::new(&b"\x0b\x36\x37"[..], RepeatingKey::new("ICE"));
+/// let mut plaintext = String::new();
+/// reader.read_to_string(&mut plaintext).unwrap();
+/// assert_eq!("Bur", plaintext);
+/// ```
+#[derive(Debug)]
+pub struct XorReader<R, K> {
+    inner: R,
+    keystream: K,
+    /// Keystream that has been generated but not used yet.
+    pending: Vec<u8>,
+}
+
+impl<R: Read, K: Keystream> XorReader<R, K> {
+    pub fn new(inner: R, keystream: K) -> Self {
+        XorReader {
+            inner,
+            keystream,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read, K: Keystream> Read for XorReader<R, K> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let wanted = buf.len().min(CHUNK_SIZE);
+        let start = self.pending.len();
+        if start < wanted {
+            self.pending.resize(wanted, 0);
+            let n = self.keystream.fill(&mut self.pending[start..]);
+            self.pending.truncate(start + n);
+        }
+
+        if self.pending.is_empty() {
+            return match self.inner.read(&mut buf[..1])? {
+                0 => Ok(0),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "keystream exhausted",
+                )),
+            };
+        }
+
+        let available = buf.len().min(self.pending.len());
+        let n = self.inner.read(&mut buf[..available])?;
+        xor_mut(&mut buf[..n], &self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// Wraps a writer and XORs everything written to it against a keystream.
+///
+/// Each call to [`Write::write`] consumes keystream for the whole buffer and hands it to the
+/// inner writer with [`Write::write_all`], so if the inner writer fails the keystream is left
+/// ahead of what was actually written.
+#[derive(Debug)]
+pub struct XorWriter<W, K> {
+    inner: W,
+    keystream: K,
+}
+
+impl<W: Write, K: Keystream> XorWriter<W, K> {
+    pub fn new(inner: W, keystream: K) -> Self {
+        XorWriter { inner, keystream }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write, K: Keystream> Write for XorWriter<W, K> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut chunk = [0; CHUNK_SIZE];
+        let n = buf.len().min(CHUNK_SIZE);
+        let chunk = &mut chunk[..n];
+
+        chunk.copy_from_slice(&buf[..n]);
+        apply(&mut self.keystream, chunk)?;
+        self.inner.write_all(chunk)?;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::xor_cycle;
+
+    const INPUT: &str =
+        "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+
+    #[test]
+    fn test_xor_reader() {
+        let mut reader = XorReader::new(Cursor::new(INPUT), RepeatingKey::new("ICE"));
+        let mut res = Vec::new();
+        let mut buf = [0; 5];
+
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            res.extend_from_slice(&buf[..n]);
+        }
+
+        assert_eq!(xor_cycle(INPUT, "ICE"), res);
+    }
+
+    #[test]
+    fn test_xor_writer_roundtrip() {
+        let input = INPUT.repeat(20);
+        let mut writer = XorWriter::new(Vec::new(), (0..=u8::MAX).cycle());
+        writer.write_all(input.as_bytes()).unwrap();
+        let ciphertext = writer.into_inner();
+
+        let mut reader = XorReader::new(Cursor::new(ciphertext), (0..=u8::MAX).cycle());
+        let mut plaintext = String::new();
+        reader.read_to_string(&mut plaintext).unwrap();
+
+        assert_eq!(input, plaintext);
+    }
+
+    #[test]
+    fn test_keystream_exhausted() {
+        let mut reader = XorReader::new(Cursor::new(INPUT), [1_u8, 2, 3].into_iter());
+        let mut buf = [0; 5];
+
+        assert_eq!(3, reader.read(&mut buf).unwrap());
+        assert_eq!(xor_cycle(&INPUT[..3], [1, 2, 3]), buf[..3]);
+        assert_eq!(3, reader.get_ref().position());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            reader.read(&mut buf).unwrap_err().kind()
+        );
+
+        let mut reader = XorReader::new(Cursor::new(INPUT), [1_u8, 2, 3].into_iter());
+        let mut res = Vec::new();
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            reader.read_to_end(&mut res).unwrap_err().kind()
+        );
+        assert_eq!(xor_cycle(&INPUT[..3], [1, 2, 3]), res);
+    }
+
+    #[test]
+    fn test_keystream_as_long_as_input() {
+        let mut reader = XorReader::new(Cursor::new("abc"), [1_u8, 2, 3].into_iter());
+        let mut res = Vec::new();
+
+        assert_eq!(3, reader.read_to_end(&mut res).unwrap());
+        assert_eq!(xor_cycle("abc", [1, 2, 3]), res);
+    }
+}
@@ -0,0 +1,91 @@
+//! Common interface of the challenges, and the registry of every challenge solved so far.
+//!
+//! Every `setN` module lists its challenges in a `CHALLENGES` slice, which [`registry`] puts
+//! together. A challenge is solved when the answer of its [`Output`] is its expected answer.
+
+use crate::set1;
+
+/// What a challenge produced: its answer, and the other candidates it considered, best first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Output {
+    pub answer: String,
+    pub candidates: Vec<String>,
+}
+
+impl Output {
+    pub fn new(answer: impl Into<String>) -> Self {
+        Output {
+            answer: answer.into(),
+            candidates: Vec::new(),
+        }
+    }
+
+    pub fn with_candidates(mut self, candidates: impl IntoIterator<Item = String>) -> Self {
+        self.candidates.extend(candidates);
+        self
+    }
+}
+
+pub trait Challenge: Sync {
+    fn set(&self) -> u32;
+
+    /// Number of the challenge, counting from the first one of set 1.
+    fn number(&self) -> u32;
+
+    fn title(&self) -> &'static str;
+
+    fn solve(&self) -> anyhow::Result<Output>;
+
+    /// The answer that [`Challenge::solve`] should find.
+    fn expected(&self) -> &'static str;
+
+    /// Whether `output` solves the challenge.
+    fn verify(&self, output: &Output) -> bool {
+        output.answer == self.expected()
+    }
+}
+
+/// Every challenge, sorted by number.
+pub fn registry() -> Vec<&'static dyn Challenge> {
+    let mut res = set1::CHALLENGES.to_vec();
+    res.sort_by_key(|challenge| challenge.number());
+    res
+}
+
+/// The challenge with the given number, if it's in the registry.
+pub fn find(number: u32) -> Option<&'static dyn Challenge> {
+    registry()
+        .into_iter()
+        .find(|challenge| challenge.number() == number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let numbers = registry()
+            .iter()
+            .map(|challenge| challenge.number())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 4, 5, 6], numbers);
+        assert_eq!("Single-byte XOR cipher", find(3).unwrap().title());
+        assert!(find(0).is_none());
+    }
+
+    #[test]
+    fn test_all_challenges() {
+        for challenge in registry() {
+            let output = challenge.solve().unwrap();
+            assert!(
+                challenge.verify(&output),
+                "challenge {} ({}): expected {:?}, got {:?}",
+                challenge.number(),
+                challenge.title(),
+                challenge.expected(),
+                output.answer
+            );
+        }
+    }
+}
@@ -0,0 +1,385 @@
+//! Command line of the challenges binary: argument parsing, running the selected challenges and
+//! reporting the results, as text or JSON.
+
+use std::{
+    fmt::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+
+use crate::challenge::{self, Challenge, Output};
+
+pub const USAGE: &str = "\
+Usage: matasano-challenges [--data-dir <dir>] [<command>]
+
+Commands:
+  list [--set <n>] [--json]                     List the challenges
+  run (<n>... | --set <n> | --all) [--json] [--verbose]
+                                                Run and verify challenges (default: --all)
+
+Options:
+  --data-dir <dir>  Look for data files in <dir> first
+  --json            Print the results as JSON
+  --verbose         Also print the candidates considered by every challenge
+  -h, --help        Print this message
+
+Exit status: 0 if every challenge passed, 1 if any failed, 2 on usage errors.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selection {
+    All,
+    Set(u32),
+    Numbers(Vec<u32>),
+}
+
+impl Selection {
+    /// Challenges of the registry that are selected. Fails on numbers that aren't in the
+    /// registry, and on sets without any challenge.
+    pub fn challenges(&self) -> anyhow::Result<Vec<&'static dyn Challenge>> {
+        match self {
+            Selection::All => Ok(challenge::registry()),
+            Selection::Set(set) => {
+                let challenges = challenge::registry()
+                    .into_iter()
+                    .filter(|challenge| challenge.set() == *set)
+                    .collect::<Vec<_>>();
+                if challenges.is_empty() {
+                    bail!("no challenges in set {}", set);
+                }
+                Ok(challenges)
+            }
+            Selection::Numbers(numbers) => numbers
+                .iter()
+                .map(|n| challenge::find(*n).with_context(|| format!("no challenge {}", n)))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    List(Selection),
+    Run(Selection),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub command: Command,
+    pub data_dir: Option<PathBuf>,
+    pub json: bool,
+    pub verbose: bool,
+}
+
+fn parse_number(arg: Option<String>, what: &str) -> anyhow::Result<u32> {
+    let arg = arg.with_context(|| format!("{} needs a number", what))?;
+    arg.parse()
+        .with_context(|| format!("invalid number for {}: {}", what, arg))
+}
+
+/// Parses the arguments, without the name of the binary.
+pub fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<Options> {
+    let mut args = args.into_iter();
+    let mut command = None;
+    let mut set = None;
+    let mut all = false;
+    let mut numbers = Vec::new();
+    let mut options = Options {
+        command: Command::Run(Selection::All),
+        data_dir: None,
+        json: false,
+        verbose: false,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "help" | "list" | "run" if command.is_none() => command = Some(arg),
+            "-h" | "--help" => command = Some("help".to_string()),
+            "--data-dir" => {
+                let dir = args.next().context("--data-dir needs a directory")?;
+                options.data_dir = Some(dir.into());
+            }
+            "--json" => options.json = true,
+            "--verbose" | "-v" => options.verbose = true,
+            "--set" => set = Some(parse_number(args.next(), "--set")?),
+            "--all" => all = true,
+            _ if command.as_deref() == Some("run") && !arg.starts_with('-') => {
+                numbers.push(parse_number(Some(arg), "run")?)
+            }
+            _ => bail!("unexpected argument: {}", arg),
+        }
+    }
+
+    let selection = match (set, all, numbers.is_empty()) {
+        (None, _, true) => Selection::All,
+        (Some(set), false, true) => Selection::Set(set),
+        (None, false, false) => Selection::Numbers(numbers),
+        _ => bail!("challenge numbers, --set and --all can't be combined"),
+    };
+
+    options.command = match command.as_deref() {
+        Some("help") => Command::Help,
+        Some("list") => Command::List(selection),
+        _ => Command::Run(selection),
+    };
+
+    Ok(options)
+}
+
+#[derive(Debug)]
+pub enum Status {
+    Passed,
+    Failed,
+    Error(anyhow::Error),
+}
+
+/// Outcome of running one challenge.
+pub struct Report {
+    pub challenge: &'static dyn Challenge,
+    pub status: Status,
+    pub duration: Duration,
+    pub output: Option<Output>,
+}
+
+impl Report {
+    pub fn passed(&self) -> bool {
+        matches!(self.status, Status::Passed)
+    }
+}
+
+pub fn run(challenge: &'static dyn Challenge) -> Report {
+    let start = Instant::now();
+    let res = challenge.solve();
+    let duration = start.elapsed();
+
+    match res {
+        Ok(output) => Report {
+            challenge,
+            status: if challenge.verify(&output) {
+                Status::Passed
+            } else {
+                Status::Failed
+            },
+            duration,
+            output: Some(output),
+        },
+        Err(err) => Report {
+            challenge,
+            status: Status::Error(err),
+            duration,
+            output: None,
+        },
+    }
+}
+
+/// `s` as a JSON string literal.
+fn json_string(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => write!(res, "\\u{:04x}", c as u32).unwrap(),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn json_challenge(challenge: &dyn Challenge) -> String {
+    format!(
+        "\"id\":{},\"set\":{},\"title\":{}",
+        challenge.number(),
+        challenge.set(),
+        json_string(challenge.title())
+    )
+}
+
+/// A JSON array with an object per challenge.
+pub fn list_json(challenges: &[&'static dyn Challenge]) -> String {
+    let objects = challenges
+        .iter()
+        .map(|challenge| format!("{{{}}}", json_challenge(*challenge)))
+        .collect::<Vec<_>>();
+    format!("[{}]", objects.join(","))
+}
+
+pub fn list_text(challenges: &[&'static dyn Challenge]) -> String {
+    challenges
+        .iter()
+        .map(|challenge| {
+            format!(
+                "{:>3}. {} (set {})\n",
+                challenge.number(),
+                challenge.title(),
+                challenge.set()
+            )
+        })
+        .collect()
+}
+
+/// A JSON array with an object per report. Candidates are only included if `verbose` is set.
+pub fn reports_json(reports: &[Report], verbose: bool) -> String {
+    let objects = reports
+        .iter()
+        .map(|report| {
+            let mut res = format!("{{{}", json_challenge(report.challenge));
+            let status = match &report.status {
+                Status::Passed => "passed",
+                Status::Failed => "failed",
+                Status::Error(_) => "error",
+            };
+            write!(
+                res,
+                ",\"status\":\"{}\",\"time_ms\":{:.3},\"expected\":{}",
+                status,
+                report.duration.as_secs_f64() * 1000.0,
+                json_string(report.challenge.expected())
+            )
+            .unwrap();
+
+            if let Status::Error(err) = &report.status {
+                write!(res, ",\"error\":{}", json_string(&format!("{:#}", err))).unwrap();
+            }
+            if let Some(output) = &report.output {
+                write!(res, ",\"answer\":{}", json_string(&output.answer)).unwrap();
+                if verbose {
+                    let candidates = output
+                        .candidates
+                        .iter()
+                        .map(|c| json_string(c))
+                        .collect::<Vec<_>>();
+                    write!(res, ",\"candidates\":[{}]", candidates.join(",")).unwrap();
+                }
+            }
+
+            res.push('}');
+            res
+        })
+        .collect::<Vec<_>>();
+
+    format!("[{}]", objects.join(","))
+}
+
+pub fn reports_text(reports: &[Report], verbose: bool) -> String {
+    let mut res = String::new();
+
+    for report in reports {
+        let challenge = report.challenge;
+        let status = match report.status {
+            Status::Passed => "ok",
+            Status::Failed => "FAILED",
+            Status::Error(_) => "ERROR",
+        };
+        writeln!(
+            res,
+            "[{}] {}. {} ({:.1?})",
+            status,
+            challenge.number(),
+            challenge.title(),
+            report.duration
+        )
+        .unwrap();
+
+        match (&report.status, &report.output) {
+            (Status::Error(err), _) => writeln!(res, "    {:#}", err).unwrap(),
+            (Status::Failed, Some(output)) => writeln!(
+                res,
+                "    answer:   {}\n    expected: {}",
+                output.answer,
+                challenge.expected()
+            )
+            .unwrap(),
+            (_, Some(output)) => writeln!(res, "    {}", output.answer).unwrap(),
+            _ => {}
+        }
+
+        if let Some(output) = report.output.as_ref().filter(|_| verbose) {
+            for candidate in &output.candidates {
+                for line in candidate.lines() {
+                    writeln!(res, "      | {}", line).unwrap();
+                }
+            }
+        }
+    }
+
+    let passed = reports.iter().filter(|r| r.passed()).count();
+    writeln!(res, "{}/{} passed", passed, reports.len()).unwrap();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Command::Run(Selection::All),
+            parse(args("")).unwrap().command
+        );
+        assert_eq!(
+            Command::Run(Selection::Numbers(vec![3, 6])),
+            parse(args("run 3 6")).unwrap().command
+        );
+        assert_eq!(
+            Command::Run(Selection::Set(2)),
+            parse(args("run --set 2 --json")).unwrap().command
+        );
+        assert_eq!(
+            Command::List(Selection::Set(1)),
+            parse(args("list --set 1")).unwrap().command
+        );
+
+        let options = parse(args("--data-dir /tmp run --all --verbose --json")).unwrap();
+        assert_eq!(Some(PathBuf::from("/tmp")), options.data_dir);
+        assert!(options.json && options.verbose);
+
+        assert!(parse(args("run 3 --all")).is_err());
+        assert!(parse(args("run --set")).is_err());
+        assert!(parse(args("run three")).is_err());
+        assert!(parse(args("list 3")).is_err());
+        assert!(parse(args("frobnicate")).is_err());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(r#""a\"b\\c\nd\u0001""#, json_string("a\"b\\c\nd\u{1}"));
+    }
+
+    #[test]
+    fn test_reports() {
+        let reports = Selection::Numbers(vec![1, 2])
+            .challenges()
+            .unwrap()
+            .into_iter()
+            .map(run)
+            .collect::<Vec<_>>();
+        assert!(reports.iter().all(Report::passed));
+
+        let json = reports_json(&reports, false);
+        assert!(json.starts_with(
+            r#"[{"id":1,"set":1,"title":"Convert hex to base64","status":"passed","time_ms":"#
+        ));
+        assert!(!json.contains("candidates"));
+        assert!(reports_text(&reports, true).ends_with("2/2 passed\n"));
+
+        assert!(Selection::Numbers(vec![999]).challenges().is_err());
+        assert_eq!(1, Selection::Set(1).challenges().unwrap()[0].set());
+        assert_eq!(
+            "no challenges in set 99",
+            Selection::Set(99).challenges().err().unwrap().to_string()
+        );
+    }
+}
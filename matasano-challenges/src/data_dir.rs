@@ -0,0 +1,67 @@
+//! Location of the challenge data files, so that they are found whatever the working directory.
+//!
+//! Files are looked up, in order, in the directory given to [`set`] (the `--data-dir` flag of the
+//! binary), in the directory named by the `MATASANO_DATA_DIR` environment variable, and in the
+//! `data` directory of this crate.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use matasano_util::data::{self, Format};
+
+/// Environment variable that overrides the default data directory.
+pub const ENV_VAR: &str = "MATASANO_DATA_DIR";
+
+static OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Makes `dir` the first directory searched. Only the first call has an effect.
+pub fn set(dir: impl Into<PathBuf>) {
+    let _ = OVERRIDE.set(dir.into());
+}
+
+/// Directories searched for data files, in order.
+pub fn search_path() -> Vec<PathBuf> {
+    OVERRIDE
+        .get()
+        .cloned()
+        .into_iter()
+        .chain(std::env::var_os(ENV_VAR).map(PathBuf::from))
+        .chain([Path::new(env!("CARGO_MANIFEST_DIR")).join("data")])
+        .collect()
+}
+
+/// Path of the data file `name`. The error lists every path that was tried.
+pub fn resolve(name: impl AsRef<Path>) -> Result<PathBuf, data::Error> {
+    data::find(name, search_path())
+}
+
+/// Finds and decodes the data file `name`, with the records concatenated.
+pub fn load(name: impl AsRef<Path>, format: Format) -> Result<Vec<u8>, data::Error> {
+    data::load(resolve(name)?, format)
+}
+
+/// Finds and decodes the data file `name` into its records.
+pub fn load_records(name: impl AsRef<Path>, format: Format) -> Result<Vec<Vec<u8>>, data::Error> {
+    data::load_records(resolve(name)?, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert!(resolve("4.txt")
+            .unwrap()
+            .ends_with("matasano-challenges/data/4.txt"));
+
+        let err = resolve("missing.txt").unwrap_err().to_string();
+        assert!(
+            err.contains("matasano-challenges/data/missing.txt"),
+            "{}",
+            err
+        );
+    }
+}
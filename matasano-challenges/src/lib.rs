@@ -1 +1,4 @@
+pub mod challenge;
+pub mod cli;
+pub mod data_dir;
 pub mod set1;
@@ -1,7 +1,54 @@
-use matasano_challenges::set1;
+use std::process::ExitCode;
 
-fn main() -> anyhow::Result<()> {
-    set1::run()?;
+use matasano_challenges::{
+    cli::{self, Command},
+    data_dir,
+};
 
-    Ok(())
+fn main() -> ExitCode {
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {:#}\n\n{}", err, cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Some(dir) = &options.data_dir {
+        data_dir::set(dir);
+    }
+
+    let (Command::List(selection) | Command::Run(selection)) = &options.command else {
+        println!("{}", cli::USAGE);
+        return ExitCode::SUCCESS;
+    };
+    let challenges = match selection.challenges() {
+        Ok(challenges) => challenges,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            return ExitCode::from(2);
+        }
+    };
+
+    if let Command::List(_) = options.command {
+        if options.json {
+            println!("{}", cli::list_json(&challenges));
+        } else {
+            print!("{}", cli::list_text(&challenges));
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let reports = challenges.into_iter().map(cli::run).collect::<Vec<_>>();
+    if options.json {
+        println!("{}", cli::reports_json(&reports, options.verbose));
+    } else {
+        print!("{}", cli::reports_text(&reports, options.verbose));
+    }
+
+    if reports.iter().all(cli::Report::passed) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
 }
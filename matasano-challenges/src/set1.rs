@@ -1,17 +1,24 @@
+use anyhow::Context;
+use matasano_bitwise::break_xor::{
+    break_repeating_key_xor, detect_single_byte_xor, rank_single_byte_xor, Candidate, Detection,
+};
+use matasano_constants::{
+    score::{Scorer, Weighted},
+    ENGLISH_WORDS,
+};
+use matasano_util::{data::Format, Bytes};
+
+use crate::{
+    challenge::{Challenge, Output},
+    data_dir,
+};
+
 //--------------------------------------------------------------------------
 // BEGIN NOTE
 //
 // These methods will soon be replaced by matasano-parser. I'm using
 // them for now to have a quick (and naive) way of deciding if a string
-// is garbage or not. It worked well for Challenge 3, but not so well for
-// Challenge 4 (there were 2 false positives).
-fn is_special_word(xs: &str) -> bool {
-    xs == "pneumonoultramicroscopicsilicovolcanoconiosis"
-        || xs == "supercalifragilisticexpialidocious"
-        || xs == "pseudopseudohypoparathyroidism"
-        || xs == "antidisestablishmentarianism"
-        || xs == "honorificabilitudinitatibus"
-}
+// is garbage or not.
 
 /// Checks whether a string represents a number.
 fn is_number(xs: &str) -> bool {
@@ -23,41 +30,41 @@ fn is_number(xs: &str) -> bool {
         || xs.parse::<f64>().is_ok()
 }
 
-/// Very naive way to check if a string is a valid word.
-fn is_regular_word(xs: &str) -> bool {
-    let mut hyphen = 0;
-    let mut quote = false;
-
-    for c in xs.chars() {
-        if c == '-' {
-            if hyphen == 2 {
-                return false;
-            } else {
-                hyphen += 1;
-            }
-        } else if c == '\'' {
-            if quote {
-                return false;
-            } else {
-                quote = true;
-            }
-        } else if !c.is_alphabetic() {
-            return false;
-        }
+/// Checks whether a string is a word of the English dictionary, ignoring leading and trailing
+/// punctuation. Contractions and clipped words are reduced to their stem first, so that "I'm",
+/// "don't" or "ringin'" count as words.
+fn is_dictionary_word(xs: &str) -> bool {
+    let word = xs
+        .trim_matches(|c: char| (c.is_ascii_punctuation() && c != '\'') || c.is_whitespace())
+        .trim_start_matches('\'');
+    if let Some(stem) = word.strip_suffix("in'") {
+        return ENGLISH_WORDS.contains(format!("{}ing", stem));
     }
 
-    true
+    let word = word.trim_end_matches('\'');
+    if ENGLISH_WORDS.contains(word) {
+        return true;
+    }
+
+    // "can't" and "won't" lose an `n` along with the `o` of "not".
+    if let Some(stem) = word.strip_suffix("n't") {
+        return ENGLISH_WORDS.contains(stem) || ENGLISH_WORDS.contains(format!("{}n", stem));
+    }
+    ["'s", "'ll", "'re", "'ve", "'d", "'m"]
+        .iter()
+        .filter_map(|suffix| word.strip_suffix(suffix))
+        .any(|stem| ENGLISH_WORDS.contains(stem))
 }
 
 fn is_maybe_valid_word(xs: &str) -> bool {
-    is_special_word(xs) || is_number(xs) || is_regular_word(xs)
+    is_number(xs) || is_dictionary_word(xs)
 }
 
 fn maybe_good_string(xs: &str) -> bool {
     let mut valid_words = 0;
     let mut invalid_words = 0;
 
-    for part in xs.split(' ') {
+    for part in xs.split_whitespace() {
         if is_maybe_valid_word(part) {
             valid_words += 1;
         } else {
@@ -70,53 +77,243 @@ fn maybe_good_string(xs: &str) -> bool {
 // END NOTE
 //--------------------------------------------------------------------
 
-/// Takes as input a string that has been XOR'd against a single byte (key). It returns
+/// Takes as input a hex string that has been XOR'd against a single byte (key). It returns
 /// a Vec of tuples, where the first element of each tuple is a candidate key and
-/// the second is a candidate original string.
-pub fn single_byte_xor(input: &str) -> Vec<(u8, String)> {
-    let bytes = matasano_util::hex_str_to_bytes(input);
-    let mut res = Vec::new();
+/// the second is the corresponding plaintext. Every key is tried, and candidates are sorted by
+/// `scorer` with the most likely one first. Fails if `input` isn't valid hex.
+pub fn single_byte_xor(
+    input: &str,
+    scorer: &impl Scorer,
+) -> Result<Vec<(u8, String)>, matasano_util::Error> {
+    let bytes = matasano_util::hex_str_to_bytes(input)?;
 
-    for byte in 41..=0x7F {
-        let xored = matasano_bitwise::xor_cycle(&bytes, [byte]);
-        let xored_ascii = String::from_utf8_lossy(&xored);
+    Ok(rank_single_byte_xor(bytes, scorer, 256)
+        .into_iter()
+        .map(|(key, plaintext, _)| (key, String::from_utf8_lossy(&plaintext).into_owned()))
+        .collect())
+}
 
-        if maybe_good_string(xored_ascii.trim()) {
-            res.push((byte, xored_ascii.trim().to_string()));
-        }
+/// Finds the line of `4.txt` that has been encrypted with single-byte XOR.
+pub fn detect_single_char_xor() -> anyhow::Result<Detection> {
+    let ciphertexts = data_dir::load_records("4.txt", Format::HexLines)?;
+
+    detect_single_byte_xor(&ciphertexts, &Weighted::english()).context("no ciphertext")
+}
+
+pub fn break_repeating_key_xor_file() -> anyhow::Result<Vec<Candidate>> {
+    let ciphertext = data_dir::load("6.txt", Format::Base64)?;
+
+    Ok(break_repeating_key_xor(ciphertext, &Weighted::english()))
+}
+
+pub struct HexToBase64;
+
+impl Challenge for HexToBase64 {
+    fn set(&self) -> u32 {
+        1
+    }
+
+    fn number(&self) -> u32 {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Convert hex to base64"
     }
 
-    res
+    fn solve(&self) -> anyhow::Result<Output> {
+        let bytes: Bytes =
+            "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d"
+                .parse()?;
+
+        Ok(Output::new(bytes.to_base64()))
+    }
+
+    fn expected(&self) -> &'static str {
+        "SSdtIGtpbGxpbmcgeW91ciBicmFpbiBsaWtlIGEgcG9pc29ub3VzIG11c2hyb29t"
+    }
 }
 
-pub fn detect_single_char_xor() -> anyhow::Result<()> {
-    let data = matasano_util::get_file_contents("./data/4.txt")?;
+pub struct FixedXor;
 
-    // Need to improve the algorithm for detecting valid sentences. There are two false positives.
-    // The correct sentence is also printed: "Now that the party is jumping"
-    for line in data {
-        for (key, candidate) in single_byte_xor(&line) {
-            println!("(key: {}) {}", key, candidate);
-        }
+impl Challenge for FixedXor {
+    fn set(&self) -> u32 {
+        1
+    }
+
+    fn number(&self) -> u32 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Fixed XOR"
     }
 
-    Ok(())
+    fn solve(&self) -> anyhow::Result<Output> {
+        let a: Bytes = "1c0111001f010100061a024b53535009181c".parse()?;
+        let b: Bytes = "686974207468652062756c6c277320657965".parse()?;
+
+        Ok(Output::new((&a ^ &b).to_string()))
+    }
+
+    fn expected(&self) -> &'static str {
+        "746865206b696420646f6e277420706c6179"
+    }
 }
 
-pub fn run() -> anyhow::Result<()> {
-    println!("====== 3. Single-byte XOR cipher ======");
+pub struct SingleByteXor;
 
-    let input = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
-    for (key, candidate) in single_byte_xor(input) {
-        println!("(key: {}) {}", key, candidate);
+impl Challenge for SingleByteXor {
+    fn set(&self) -> u32 {
+        1
     }
 
-    println!("====== 4. Detect single-character XOR ======");
-    detect_single_char_xor()?;
+    fn number(&self) -> u32 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Single-byte XOR cipher"
+    }
+
+    fn solve(&self) -> anyhow::Result<Output> {
+        let ciphertext = matasano_util::hex_str_to_bytes(
+            "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736",
+        )?;
+        let candidates = rank_single_byte_xor(ciphertext, &Weighted::english(), 5);
+        let (_, answer, _) = candidates.first().context("no candidate")?;
+
+        Ok(
+            Output::new(String::from_utf8_lossy(answer)).with_candidates(candidates.iter().map(
+                |(key, plaintext, score)| {
+                    let plaintext = String::from_utf8_lossy(plaintext);
+                    format!(
+                        "(key: {}, score: {:.2}, readable: {}) {}",
+                        key,
+                        score,
+                        maybe_good_string(&plaintext),
+                        plaintext
+                    )
+                },
+            )),
+        )
+    }
 
-    Ok(())
+    fn expected(&self) -> &'static str {
+        "Cooking MC's like a pound of bacon"
+    }
 }
 
+pub struct DetectSingleCharXor;
+
+impl Challenge for DetectSingleCharXor {
+    fn set(&self) -> u32 {
+        1
+    }
+
+    fn number(&self) -> u32 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Detect single-character XOR"
+    }
+
+    fn solve(&self) -> anyhow::Result<Output> {
+        let detection = detect_single_char_xor()?;
+        let answer = String::from_utf8_lossy(&detection.plaintext);
+
+        Ok(Output::new(answer.trim()).with_candidates([format!(
+            "(line: {}, key: {}, margin: {:.2}) {}",
+            detection.index + 1,
+            detection.key,
+            detection.margin,
+            answer.trim()
+        )]))
+    }
+
+    fn expected(&self) -> &'static str {
+        "Now that the party is jumping"
+    }
+}
+
+pub struct RepeatingKeyXor;
+
+impl Challenge for RepeatingKeyXor {
+    fn set(&self) -> u32 {
+        1
+    }
+
+    fn number(&self) -> u32 {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Implement repeating-key XOR"
+    }
+
+    fn solve(&self) -> anyhow::Result<Output> {
+        let plaintext =
+            "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+        let ciphertext = matasano_bitwise::xor_cycle(plaintext, "ICE");
+
+        Ok(Output::new(matasano_util::bytes_to_hex_str(ciphertext)))
+    }
+
+    fn expected(&self) -> &'static str {
+        "0b3637272a2b2e63622c2e69692a23693a2a3c6324202d623d63343c2a26226324272765272a282b2f20430a652e2c652a3124333a653e2b2027630c692b20283165286326302e27282f"
+    }
+}
+
+pub struct BreakRepeatingKeyXor;
+
+impl Challenge for BreakRepeatingKeyXor {
+    fn set(&self) -> u32 {
+        1
+    }
+
+    fn number(&self) -> u32 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Break repeating-key XOR"
+    }
+
+    /// The answer is the key, and the candidates are every key tried with its plaintext.
+    fn solve(&self) -> anyhow::Result<Output> {
+        let candidates = break_repeating_key_xor_file()?;
+        let (key, _, _) = candidates.first().context("no candidate")?;
+
+        Ok(
+            Output::new(String::from_utf8_lossy(key)).with_candidates(candidates.iter().map(
+                |(key, plaintext, _)| {
+                    let plaintext = String::from_utf8_lossy(plaintext);
+                    format!(
+                        "(key: {}, readable: {})\n{}",
+                        String::from_utf8_lossy(key),
+                        maybe_good_string(&plaintext),
+                        plaintext
+                    )
+                },
+            )),
+        )
+    }
+
+    fn expected(&self) -> &'static str {
+        "Terminator X: Bring the noise"
+    }
+}
+
+pub const CHALLENGES: &[&dyn Challenge] = &[
+    &HexToBase64,
+    &FixedXor,
+    &SingleByteXor,
+    &DetectSingleCharXor,
+    &RepeatingKeyXor,
+    &BreakRepeatingKeyXor,
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,4 +326,77 @@ mod tests {
         assert!(is_number("123abc"));
         assert!(!is_number("123abc.456"));
     }
+
+    #[test]
+    fn test_is_dictionary_word() {
+        assert!(is_dictionary_word("Cooking"));
+        assert!(is_dictionary_word("jumping\n"));
+        assert!(is_dictionary_word("bacon,"));
+        assert!(!is_dictionary_word("xqzt"));
+        assert!(!is_dictionary_word(""));
+
+        for word in [
+            "ringin'", "yellin',", "'cause", "I'm", "don't", "can't", "That's",
+        ] {
+            assert!(is_dictionary_word(word), "{}", word);
+        }
+        assert!(!is_dictionary_word("xqzt'"));
+        assert!(maybe_good_string(
+            "I'm back and I'm ringin' the bell \nA rockin' on the mike while the fly girls yell \n"
+        ));
+    }
+
+    #[test]
+    fn test_single_byte_xor() {
+        let input = "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736";
+        assert_eq!(
+            Some((88, "Cooking MC's like a pound of bacon".to_string())),
+            single_byte_xor(input, &Weighted::english())
+                .unwrap()
+                .into_iter()
+                .next()
+        );
+        assert_eq!(
+            256,
+            single_byte_xor(input, &Weighted::english()).unwrap().len()
+        );
+        assert!(single_byte_xor("1b3", &Weighted::english()).is_err());
+    }
+
+    #[test]
+    fn test_detect_single_char_xor() {
+        let detection = detect_single_char_xor().unwrap();
+
+        assert_eq!(53, detection.key);
+        assert_eq!(
+            "Now that the party is jumping",
+            String::from_utf8_lossy(&detection.plaintext).trim()
+        );
+    }
+
+    #[test]
+    fn test_detect_single_byte_xor() {
+        let ciphertexts = data_dir::load_records("4.txt", Format::HexLines).unwrap();
+        let detection = detect_single_byte_xor(&ciphertexts, &Weighted::english()).unwrap();
+
+        assert_eq!((170, 53), (detection.index, detection.key));
+        assert!(detection.margin > 10.0, "{}", detection.margin);
+    }
+
+    #[test]
+    fn test_break_repeating_key_xor() {
+        let candidates = break_repeating_key_xor_file().unwrap();
+        let (key, plaintext, _) = candidates.first().unwrap();
+
+        assert_eq!(b"Terminator X: Bring the noise".to_vec(), *key);
+        assert!(String::from_utf8_lossy(plaintext).starts_with("I'm back and I'm ringin' the bell"));
+    }
+
+    #[test]
+    fn test_embedded_data() {
+        assert_eq!(
+            data_dir::load_records("4.txt", Format::HexLines).unwrap(),
+            matasano_util::include_records!("../data/4.txt", Format::HexLines).unwrap()
+        );
+    }
 }
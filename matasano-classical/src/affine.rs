@@ -0,0 +1,98 @@
+//! Affine cipher: the letter at position `x` of the alphabet is replaced by the letter at
+//! position `a * x + b`, modulo 26.
+
+use matasano_constants::{
+    language::Language,
+    score::{ChiSquared, Scorer},
+};
+
+use crate::map_letters;
+
+/// Multiplicative inverse of `a` modulo 26, if it has one.
+fn inverse(a: u8) -> Option<u8> {
+    (1..26).find(|x| (u32::from(a) * u32::from(*x)) % 26 == 1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    a: u8,
+    b: u8,
+}
+
+impl Key {
+    /// Returns `None` if `a` isn't coprime with 26, since the cipher couldn't be reversed.
+    pub fn new(a: u8, b: u8) -> Option<Self> {
+        let (a, b) = (a % 26, b % 26);
+        inverse(a).map(|_| Key { a, b })
+    }
+
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+
+    /// Every valid key, 312 of them.
+    pub fn all() -> impl Iterator<Item = Key> {
+        (0..26).flat_map(|a| (0..26).filter_map(move |b| Key::new(a, b)))
+    }
+}
+
+pub fn encrypt(text: &str, key: &Key) -> String {
+    map_letters(text, |x| {
+        ((u32::from(key.a) * u32::from(x) + u32::from(key.b)) % 26) as u8
+    })
+}
+
+pub fn decrypt(text: &str, key: &Key) -> String {
+    let a_inv = u32::from(inverse(key.a).unwrap());
+    map_letters(text, |y| {
+        ((a_inv * (u32::from(y) + 26 - u32::from(key.b))) % 26) as u8
+    })
+}
+
+/// Tries every key and keeps the one whose output has the closest letter frequencies to
+/// `language`. Returns the key and the plaintext.
+pub fn solve(ciphertext: &str, language: Language) -> (Key, String) {
+    let scorer = ChiSquared::new(language);
+
+    Key::all()
+        .map(|key| (key, decrypt(ciphertext, &key)))
+        .max_by(|(_, a), (_, b)| {
+            scorer
+                .score(a.as_bytes())
+                .total_cmp(&scorer.score(b.as_bytes()))
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(None, Key::new(13, 1));
+        assert_eq!(312, Key::all().count());
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let key = Key::new(5, 8).unwrap();
+        assert_eq!("Ihhwvc Swfrcp", encrypt("Affine Cipher", &key));
+        assert_eq!("Affine Cipher", decrypt("Ihhwvc Swfrcp", &key));
+    }
+
+    #[test]
+    fn test_solve() {
+        let plaintext = "Frequency analysis breaks every cipher that maps each letter to another \
+                         single letter, as long as the text is not too short.";
+        let key = Key::new(7, 3).unwrap();
+        assert_eq!(
+            (key, plaintext.to_string()),
+            solve(&encrypt(plaintext, &key), Language::English)
+        );
+    }
+}
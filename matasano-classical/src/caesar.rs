@@ -0,0 +1,52 @@
+//! Caesar cipher: every letter is shifted by the same amount.
+
+use matasano_constants::{
+    language::Language,
+    score::{ChiSquared, Scorer},
+};
+
+use crate::map_letters;
+
+pub fn encrypt(text: &str, shift: u8) -> String {
+    let shift = shift % 26;
+    map_letters(text, |c| (c + shift) % 26)
+}
+
+pub fn decrypt(text: &str, shift: u8) -> String {
+    encrypt(text, 26 - shift % 26)
+}
+
+/// Tries the 26 shifts and keeps the one whose output has the closest letter frequencies to
+/// `language`. Returns the shift and the plaintext.
+pub fn solve(ciphertext: &str, language: Language) -> (u8, String) {
+    let scorer = ChiSquared::new(language);
+
+    (0..26)
+        .map(|shift| (shift, decrypt(ciphertext, shift)))
+        .max_by(|(_, a), (_, b)| {
+            scorer
+                .score(a.as_bytes())
+                .total_cmp(&scorer.score(b.as_bytes()))
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt() {
+        assert_eq!("Khoor, Zruog!", encrypt("Hello, World!", 3));
+        assert_eq!("Hello, World!", decrypt("Khoor, Zruog!", 29));
+    }
+
+    #[test]
+    fn test_solve() {
+        let plaintext = "The quick brown fox jumps over the lazy dog and keeps running";
+        assert_eq!(
+            (11, plaintext.to_string()),
+            solve(&encrypt(plaintext, 11), Language::English)
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Classical ciphers, and solvers that break them with the statistics of
+//! [`matasano_constants`]: frequency analysis for ciphers with few keys, and n-gram hill
+//! climbing or simulated annealing for the ones with too many keys to try them all.
+//!
+//! Unless stated otherwise, ciphers only touch ASCII letters, preserve their case and leave
+//! everything else alone.
+
+pub mod affine;
+pub mod caesar;
+pub mod playfair;
+pub mod substitution;
+pub mod vigenere;
+
+/// Replaces every ASCII letter of `text` by `f` applied to its position in the alphabet,
+/// preserving its case. `f` must return a position between 0 and 25.
+fn map_letters(text: &str, mut f: impl FnMut(u8) -> u8) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_lowercase() {
+                (b'a' + f(c as u8 - b'a')) as char
+            } else if c.is_ascii_uppercase() {
+                (b'A' + f(c as u8 - b'A')) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Positions in the alphabet of the ASCII letters of `text`.
+fn letter_positions(text: &str) -> Vec<u8> {
+    text.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase() - b'a')
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_letters() {
+        assert_eq!("Bcd, E!", map_letters("Abc, D!", |c| (c + 1) % 26));
+        assert_eq!(vec![0, 25, 1], letter_positions("a-Z b"));
+    }
+}
@@ -0,0 +1,203 @@
+//! Simple substitution cipher: every letter of the alphabet is replaced by another one, according
+//! to a permutation of the alphabet.
+
+use std::fmt;
+
+use matasano_constants::{language::Language, ngram::NgramTable};
+use matasano_util::rng::Rng;
+
+use crate::{letter_positions, map_letters};
+
+/// Number of random restarts of the hill climbing in [`solve`].
+const RESTARTS: usize = 10;
+
+/// A permutation of the alphabet: the letter at position `i` is replaced by the letter at
+/// position `key[i]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key([u8; 26]);
+
+impl Key {
+    /// Builds a key from the cipher alphabet, the 26 letters in the order that replaces `a` to
+    /// `z`. Returns `None` if `alphabet` isn't a permutation of the alphabet.
+    pub fn new(alphabet: &str) -> Option<Self> {
+        let positions = letter_positions(alphabet);
+        if alphabet.len() != 26 || positions.len() != 26 {
+            return None;
+        }
+
+        let mut seen = [false; 26];
+        for p in &positions {
+            if std::mem::replace(&mut seen[*p as usize], true) {
+                return None;
+            }
+        }
+
+        Some(Key(positions.try_into().unwrap()))
+    }
+
+    /// Builds the cipher alphabet from the distinct letters of `keyword`, followed by the rest of
+    /// the alphabet in order.
+    pub fn from_keyword(keyword: &str) -> Self {
+        let mut key = Vec::with_capacity(26);
+        for p in letter_positions(keyword).into_iter().chain(0..26) {
+            if !key.contains(&p) {
+                key.push(p);
+            }
+        }
+
+        Key(key.try_into().unwrap())
+    }
+
+    /// The key that reverses this one.
+    pub fn inverse(&self) -> Self {
+        let mut res = [0; 26];
+        for (i, p) in self.0.iter().enumerate() {
+            res[*p as usize] = i as u8;
+        }
+        Key(res)
+    }
+}
+
+/// The cipher alphabet, in lowercase.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in self.0 {
+            write!(f, "{}", (b'a' + p) as char)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn encrypt(text: &str, key: &Key) -> String {
+    map_letters(text, |c| key.0[c as usize])
+}
+
+pub fn decrypt(text: &str, key: &Key) -> String {
+    encrypt(text, &key.inverse())
+}
+
+/// N-gram fitness of `letters` (alphabet positions) decrypted with the inverse key `dec`.
+fn fitness(ngrams: &NgramTable, letters: &[u8], dec: &[u8; 26], buf: &mut Vec<u8>) -> f64 {
+    buf.clear();
+    buf.extend(letters.iter().map(|c| b'a' + dec[*c as usize]));
+    ngrams.fitness(buf)
+}
+
+/// Swaps every pair of letters of `dec` in turn, keeping the swaps that improve the fitness,
+/// until no swap does. Returns the final fitness.
+fn hill_climb(ngrams: &NgramTable, letters: &[u8], dec: &mut [u8; 26]) -> f64 {
+    let mut buf = Vec::with_capacity(letters.len());
+    let mut best = fitness(ngrams, letters, dec, &mut buf);
+    let mut improved = true;
+
+    while improved {
+        improved = false;
+        for i in 0..26 {
+            for j in i + 1..26 {
+                dec.swap(i, j);
+                let score = fitness(ngrams, letters, dec, &mut buf);
+                if score > best {
+                    best = score;
+                    improved = true;
+                } else {
+                    dec.swap(i, j);
+                }
+            }
+        }
+    }
+
+    best
+}
+
+/// Breaks a simple substitution with hill climbing over n-gram fitness. The first climb starts
+/// from the key that matches the letter frequencies of the ciphertext with those of `language`,
+/// and the others from random keys drawn from `rng`. Returns the key and the plaintext.
+///
+/// Needs a few hundred letters of ciphertext to be reliable.
+pub fn solve(ciphertext: &str, language: Language, rng: &mut impl Rng) -> (Key, String) {
+    climb(ciphertext, language, RESTARTS, rng)
+}
+
+/// [`solve`] with `restarts` climbs.
+fn climb(
+    ciphertext: &str,
+    language: Language,
+    restarts: usize,
+    rng: &mut impl Rng,
+) -> (Key, String) {
+    let model = language.model();
+    let letters = letter_positions(ciphertext);
+
+    let mut by_frequency = (0..26_u8).collect::<Vec<_>>();
+    by_frequency.sort_by_key(|p| std::cmp::Reverse(letters.iter().filter(|c| *c == p).count()));
+
+    let mut expected = (0..26_u8).collect::<Vec<_>>();
+    expected.sort_by(|a, b| {
+        let freq = |p: &u8| {
+            model
+                .monograms
+                .get(&((b'a' + p) as char))
+                .copied()
+                .unwrap_or_default()
+        };
+        freq(b).total_cmp(&freq(a))
+    });
+
+    let mut dec = [0; 26];
+    for (c, p) in by_frequency.iter().zip(&expected) {
+        dec[*c as usize] = *p;
+    }
+
+    let mut best = (f64::NEG_INFINITY, dec);
+
+    for _ in 0..restarts {
+        let score = hill_climb(model.ngrams(), &letters, &mut dec);
+        if score > best.0 {
+            best = (score, dec);
+        }
+        rng.shuffle(&mut dec);
+    }
+
+    let key = Key(best.1).inverse();
+    (key, decrypt(ciphertext, &key))
+}
+
+#[cfg(test)]
+mod tests {
+    use matasano_util::rng::XorShift;
+
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(None, Key::new("abc"));
+        assert_eq!(None, Key::new("aacdefghijklmnopqrstuvwxyz"));
+        assert_eq!(
+            "zebrascdfghijklmnopqtuvwxy",
+            Key::from_keyword("Zebras").to_string()
+        );
+        let key = Key::new("qwertyuiopasdfghjklzxcvbnm").unwrap();
+        assert_eq!(key, key.inverse().inverse());
+    }
+
+    #[test]
+    fn test_encrypt() {
+        let key = Key::from_keyword("zebras");
+        assert_eq!("Siaa zq lkba!", encrypt("Flee at once!", &key));
+        assert_eq!("Flee at once!", decrypt("Siaa zq lkba!", &key));
+    }
+
+    #[test]
+    fn test_solve() {
+        let plaintext = include_str!("../../testdata/english.txt");
+        let key = Key::new("qwertyuiopasdfghjklzxcvbnm").unwrap();
+        let (_, decrypted) = climb(
+            &encrypt(plaintext, &key),
+            Language::English,
+            2,
+            &mut XorShift::new(0x5EED),
+        );
+
+        assert_eq!(plaintext, decrypted);
+    }
+}
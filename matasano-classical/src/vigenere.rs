@@ -0,0 +1,113 @@
+//! Vigenère cipher: every letter is shifted by the corresponding letter of a repeating key.
+//! The key only advances on letters.
+
+use matasano_bitwise::period;
+use matasano_constants::{language::Language, score::Scorer};
+use matasano_util::par;
+
+use crate::{caesar, letter_positions, map_letters};
+
+/// Shifts the letters of `text` by the repeating `shifts`. No shifts leave `text` as it is.
+fn apply(text: &str, shifts: &[u8]) -> String {
+    if shifts.is_empty() {
+        return text.to_string();
+    }
+
+    let mut i = 0;
+    map_letters(text, |c| {
+        let shift = shifts[i % shifts.len()];
+        i += 1;
+        (c + shift) % 26
+    })
+}
+
+/// Non-letters of `key` are ignored, and an empty key leaves `text` as it is.
+pub fn encrypt(text: &str, key: &str) -> String {
+    apply(text, &letter_positions(key))
+}
+
+pub fn decrypt(text: &str, key: &str) -> String {
+    let shifts = letter_positions(key)
+        .iter()
+        .map(|shift| (26 - shift) % 26)
+        .collect::<Vec<_>>();
+    apply(text, &shifts)
+}
+
+/// Shortest prefix of `key` that repeats to `key`, since multiples of the key length can look as
+/// good as the key length itself.
+fn shortest_period(key: &str) -> &str {
+    (1..=key.len())
+        .filter(|len| key.len().is_multiple_of(*len))
+        .map(|len| &key[..len])
+        .find(|prefix| prefix.repeat(key.len() / prefix.len()) == key)
+        .unwrap_or(key)
+}
+
+/// Breaks a Vigenère ciphertext whose key has at most `max_keylen` letters.
+///
+/// The best key lengths by index of coincidence are tried. For each one, every column of the
+/// ciphertext is solved as a Caesar cipher, and the key whose plaintext has the best n-gram score
+/// (see [`LanguageModel::ngrams`](matasano_constants::language::LanguageModel::ngrams)) is kept.
+/// Returns the lowercase key and the plaintext.
+pub fn solve(ciphertext: &str, language: Language, max_keylen: usize) -> (String, String) {
+    let letters = letter_positions(ciphertext);
+    let ngrams = language.model().ngrams();
+
+    let keylens = period::rank_by_index_of_coincidence(&letters, 1..=max_keylen)
+        .into_iter()
+        .take(3)
+        .map(|(keylen, _)| keylen)
+        .collect::<Vec<_>>();
+
+    par::par_max_by(
+        &keylens,
+        |keylen| {
+            let mut columns = vec![String::new(); *keylen];
+            for (i, c) in letters.iter().enumerate() {
+                columns[i % keylen].push((b'a' + c) as char);
+            }
+
+            let key = columns
+                .iter()
+                .map(|column| (b'a' + caesar::solve(column, language).0) as char)
+                .collect::<String>();
+            let key = shortest_period(&key).to_string();
+            let plaintext = decrypt(ciphertext, &key);
+            let score = ngrams.score(plaintext.as_bytes());
+
+            (key, plaintext, score)
+        },
+        |(_, _, a), (_, _, b)| a.total_cmp(b),
+    )
+    .map(|(_, (key, plaintext, _))| (key, plaintext))
+    .unwrap_or_else(|| (String::new(), ciphertext.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt() {
+        assert_eq!("Lxfopv ef rnhr", encrypt("Attack at dawn", "LEMON"));
+        assert_eq!("Attack at dawn", decrypt("Lxfopv ef rnhr", "lemon"));
+        assert_eq!("abc", encrypt("abc", "123"));
+    }
+
+    #[test]
+    fn test_shortest_period() {
+        assert_eq!("abc", shortest_period("abcabcabc"));
+        assert_eq!("abcab", shortest_period("abcab"));
+        assert_eq!("", shortest_period(""));
+    }
+
+    #[test]
+    fn test_solve() {
+        let plaintext = include_str!("../../testdata/english.txt");
+        let (key, decrypted) = solve(&encrypt(plaintext, "cipher"), Language::English, 20);
+
+        assert_eq!("cipher", key);
+        assert_eq!(plaintext, decrypted);
+    }
+}
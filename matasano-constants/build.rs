@@ -0,0 +1,99 @@
+//! Generates the n-gram log-probability tables from the vendored corpora in `data/corpus`.
+//! Accented letters are folded to their unaccented equivalent first, see `src/fold.rs`.
+//!
+//! Every table is written to `OUT_DIR` as a flat array of little-endian `f32`s. The first value
+//! is the floor used for unseen n-grams and the rest are indexed by the letters of the n-gram in
+//! base 26, so `ab` is at position `1 + 0 * 26 + 1`.
+//!
+//! Byte frequencies are written as 256 little-endian `f64`s, with add-one smoothing so that
+//! bytes that never appear in the corpus still have a non-zero probability.
+//!
+//! Word lists in `data/words` are compiled into tries, see `src/trie.rs`.
+
+use std::{env, fs, path::Path};
+
+#[path = "src/fold.rs"]
+mod fold;
+
+#[allow(dead_code)]
+#[path = "src/trie.rs"]
+mod trie;
+
+/// Corpora with the length of the longest n-grams to generate for them. A corpus of a few
+/// thousand letters leaves almost every one of the 26^4 quadgrams unseen, so the languages
+/// other than English stop at trigrams.
+const CORPORA: &[(&str, usize)] = &[("en", 4), ("fr", 3), ("de", 3), ("es", 3), ("pt", 3)];
+const WORD_LISTS: &[&str] = &["en"];
+
+fn letters(corpus: &str) -> Vec<usize> {
+    fold::fold_letters(corpus.as_bytes())
+        .into_iter()
+        .map(|c| (c - b'a') as usize)
+        .collect()
+}
+
+fn ngram_table(letters: &[usize], n: usize) -> Vec<u8> {
+    let mut counts = vec![0_u32; 26_usize.pow(n as u32)];
+    for window in letters.windows(n) {
+        counts[window.iter().fold(0, |acc, l| acc * 26 + l)] += 1;
+    }
+
+    let total = counts.iter().map(|c| f64::from(*c)).sum::<f64>();
+    let floor = (0.01 / total).log10();
+
+    std::iter::once(floor)
+        .chain(counts.iter().map(|c| {
+            if *c == 0 {
+                floor
+            } else {
+                (f64::from(*c) / total).log10()
+            }
+        }))
+        .flat_map(|p| (p as f32).to_le_bytes())
+        .collect()
+}
+
+fn byte_table(corpus: &[u8]) -> Vec<u8> {
+    let mut counts = [1_u64; 256];
+    for byte in corpus {
+        counts[*byte as usize] += 1;
+    }
+
+    let total = counts.iter().sum::<u64>() as f64;
+    counts
+        .iter()
+        .flat_map(|c| (*c as f64 / total).to_le_bytes())
+        .collect()
+}
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    println!("cargo:rerun-if-changed=data/corpus");
+
+    for (name, max_n) in CORPORA {
+        let path = format!("data/corpus/{}.txt", name);
+        println!("cargo:rerun-if-changed={}", path);
+
+        let corpus = fs::read_to_string(&path).unwrap();
+        let letters = letters(&corpus);
+
+        let dest = Path::new(&out_dir).join(format!("{}_bytes.bin", name));
+        fs::write(dest, byte_table(corpus.as_bytes())).unwrap();
+
+        for n in 2..=*max_n {
+            let dest = Path::new(&out_dir).join(format!("{}_{}grams.bin", name, n));
+            fs::write(dest, ngram_table(&letters, n)).unwrap();
+        }
+    }
+
+    println!("cargo:rerun-if-changed=data/words");
+
+    for name in WORD_LISTS {
+        let path = format!("data/words/{}.txt", name);
+        println!("cargo:rerun-if-changed={}", path);
+
+        let words = fs::read_to_string(&path).unwrap();
+        let dest = Path::new(&out_dir).join(format!("{}_words.bin", name));
+        fs::write(dest, trie::Trie::from_words(words.lines()).to_le_bytes()).unwrap();
+    }
+}
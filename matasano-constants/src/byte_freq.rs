@@ -0,0 +1,145 @@
+//! Frequency of every byte value in prose, spaces, capitals, digits and punctuation included,
+//! generated at build time from the corpora in `data/corpus`. Unlike [`crate::MONOGRAM_FREQ`]
+//! these work directly on decrypted bytes, without converting them to a `String` first.
+
+use lazy_static::lazy_static;
+
+use crate::score::Scorer;
+
+/// Decodes the byte frequencies of language `$lang` generated by the build script.
+macro_rules! byte_table {
+    ($lang:literal) => {
+        $crate::byte_freq::from_le_bytes(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/",
+            $lang,
+            "_bytes.bin"
+        )))
+    };
+}
+
+pub(crate) use byte_table;
+
+lazy_static! {
+    /// Probability of every byte value in English prose.
+    pub static ref BYTE_FREQ: [f64; 256] = byte_table!("en");
+}
+
+pub(crate) fn from_le_bytes(bytes: &[u8]) -> [f64; 256] {
+    let mut res = [0_f64; 256];
+    for (dst, chunk) in res.iter_mut().zip(bytes.chunks_exact(8)) {
+        *dst = f64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    res
+}
+
+/// Relative frequency of every byte value in `bytes`. All zeros if `bytes` is empty.
+pub fn byte_frequencies(bytes: &[u8]) -> [f64; 256] {
+    let mut res = [0_f64; 256];
+    for byte in bytes {
+        res[*byte as usize] += 1.0;
+    }
+
+    if !bytes.is_empty() {
+        let total = bytes.len() as f64;
+        for v in res.iter_mut() {
+            *v /= total;
+        }
+    }
+
+    res
+}
+
+/// Bhattacharyya distance between two byte distributions. The lower the value, the more similar
+/// they are. Returns infinity if they have no byte in common.
+pub fn byte_bhattacharyya_distance(a: &[f64; 256], b: &[f64; 256]) -> f64 {
+    -a.iter()
+        .zip(b.iter())
+        .map(|(p, q)| f64::sqrt(p * q))
+        .sum::<f64>()
+        .ln()
+}
+
+/// Pearson's chi-squared statistic of the bytes of `bytes` against the `expected` distribution.
+/// Bytes with an expected probability of zero are skipped. Empty input has a statistic of 0.
+pub fn byte_chi_squared(bytes: &[u8], expected: &[f64; 256]) -> f64 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let n = bytes.len() as f64;
+    let observed = byte_frequencies(bytes);
+
+    observed
+        .iter()
+        .zip(expected.iter())
+        .filter(|(_, e)| **e > 0.0)
+        .map(|(o, e)| (o * n - e * n).powi(2) / (e * n))
+        .sum()
+}
+
+/// Average natural log-likelihood per byte of `bytes` under the `model` distribution. This is the
+/// most discriminating of the three metrics when ranking short candidate plaintexts.
+pub fn byte_log_likelihood(bytes: &[u8], model: &[f64; 256]) -> f64 {
+    if bytes.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+
+    bytes.iter().map(|b| model[*b as usize].ln()).sum::<f64>() / bytes.len() as f64
+}
+
+/// Scores candidate plaintexts with [`byte_log_likelihood`] under a byte distribution, English
+/// prose ([`BYTE_FREQ`]) by default.
+#[derive(Debug, Clone, Copy)]
+pub struct ByteFrequency {
+    model: &'static [f64; 256],
+}
+
+impl ByteFrequency {
+    pub fn new(model: &'static [f64; 256]) -> Self {
+        ByteFrequency { model }
+    }
+}
+
+impl Default for ByteFrequency {
+    fn default() -> Self {
+        ByteFrequency::new(&BYTE_FREQ)
+    }
+}
+
+impl Scorer for ByteFrequency {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        byte_log_likelihood(bytes, self.model)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_freq() {
+        assert!((BYTE_FREQ.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(BYTE_FREQ[b' ' as usize] > BYTE_FREQ[b'e' as usize]);
+        assert!(BYTE_FREQ[b'T' as usize] > BYTE_FREQ[b'Z' as usize]);
+        assert!(BYTE_FREQ[0] > 0.0);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let english = b"Cooking MC's like a pound of bacon";
+        let garbage = b"Ieeacdm*GI-y*fcao*k*zedn*el*hkied";
+
+        assert!(
+            byte_bhattacharyya_distance(&byte_frequencies(english), &BYTE_FREQ)
+                < byte_bhattacharyya_distance(&byte_frequencies(garbage), &BYTE_FREQ)
+        );
+        assert!(byte_chi_squared(english, &BYTE_FREQ) < byte_chi_squared(garbage, &BYTE_FREQ));
+        assert_eq!(0.0, byte_chi_squared(b"", &BYTE_FREQ));
+        assert!(ByteFrequency::default().score(english) > ByteFrequency::default().score(garbage));
+        assert_eq!(
+            f64::INFINITY,
+            byte_bhattacharyya_distance(&byte_frequencies(b"a"), &byte_frequencies(b"b"))
+        );
+    }
+}
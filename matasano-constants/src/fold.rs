@@ -0,0 +1,38 @@
+//! Folding of letters to the 26 letters of the basic Latin alphabet. Also used by the build
+//! script, so it must not depend on anything else in this crate.
+
+/// Maps a letter to its lowercase, unaccented ASCII equivalent (`é` becomes `e`, `ß` becomes
+/// `s`). Returns `None` for anything that isn't a letter of the supported languages.
+pub fn fold_letter(c: char) -> Option<u8> {
+    let c = c.to_lowercase().next().unwrap_or(c);
+    let folded = match c {
+        'a'..='z' => c as u8,
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' => b'a',
+        'ç' => b'c',
+        'è' | 'é' | 'ê' | 'ë' => b'e',
+        'ì' | 'í' | 'î' | 'ï' => b'i',
+        'ñ' => b'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'œ' => b'o',
+        'ß' => b's',
+        'ù' | 'ú' | 'û' | 'ü' => b'u',
+        'ý' | 'ÿ' => b'y',
+        _ => return None,
+    };
+
+    Some(folded)
+}
+
+/// Folds every letter of `text` and drops everything else. Invalid UTF-8 is ignored.
+pub fn fold_letters(text: &[u8]) -> Vec<u8> {
+    if text.is_ascii() {
+        text.iter()
+            .filter(|c| c.is_ascii_alphabetic())
+            .map(u8::to_ascii_lowercase)
+            .collect()
+    } else {
+        String::from_utf8_lossy(text)
+            .chars()
+            .filter_map(fold_letter)
+            .collect()
+    }
+}
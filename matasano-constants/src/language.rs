@@ -0,0 +1,499 @@
+//! Frequency models for the languages we may find behind a ciphertext.
+//!
+//! Monogram frequencies of languages other than English are taken from the [Wikipedia] letter
+//! frequency table and include accented letters. Word frequencies are approximate. N-gram tables
+//! and byte frequencies are generated at build time from the corpora in `data/corpus`, with
+//! accented letters folded to their unaccented equivalent in the n-grams. Only the English corpus
+//! is large enough for quadgrams.
+//!
+//! [Wikipedia]: <https://en.wikipedia.org/wiki/Letter_frequency>
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+
+use crate::{
+    bhattacharyya_distance,
+    byte_freq::{byte_table, BYTE_FREQ},
+    ngram::{ngram_table, NgramTable, BIGRAM_LOG_PROB, QUADGRAM_LOG_PROB, TRIGRAM_LOG_PROB},
+    score::{Bhattacharyya, Scorer, WordFrequency},
+    MONOGRAM_FREQ, WORD_FREQ,
+};
+
+/// A natural language with its own frequency model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    English,
+    French,
+    German,
+    Spanish,
+    Portuguese,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] = [
+        Language::English,
+        Language::French,
+        Language::German,
+        Language::Spanish,
+        Language::Portuguese,
+    ];
+
+    pub fn model(self) -> &'static LanguageModel {
+        match self {
+            Language::English => &ENGLISH,
+            Language::French => &FRENCH,
+            Language::German => &GERMAN,
+            Language::Spanish => &SPANISH,
+            Language::Portuguese => &PORTUGUESE,
+        }
+    }
+}
+
+/// Every frequency table we have for a language.
+#[derive(Debug)]
+pub struct LanguageModel {
+    pub language: Language,
+    /// Frequency of single letters, including accented ones.
+    pub monograms: &'static HashMap<char, f64>,
+    /// Frequency of the most common words.
+    pub words: &'static HashMap<&'static str, f64>,
+    pub bigrams: &'static NgramTable,
+    pub trigrams: &'static NgramTable,
+    /// Only for languages whose corpus is large enough to estimate them.
+    pub quadgrams: Option<&'static NgramTable>,
+    /// Frequency of every byte value in UTF-8 encoded prose.
+    pub bytes: &'static [f64; 256],
+}
+
+impl LanguageModel {
+    /// The longest n-grams of the language: quadgrams if it has them, trigrams otherwise. This is
+    /// the table that solvers should use as their fitness function.
+    pub fn ngrams(&self) -> &'static NgramTable {
+        self.quadgrams.unwrap_or(self.trigrams)
+    }
+
+    /// [`bhattacharyya_distance`] between `observed` and the monogram frequencies of the
+    /// language.
+    pub fn bhattacharyya_distance(&self, observed: &HashMap<char, f64>) -> f64 {
+        bhattacharyya_distance(observed, self.monograms)
+    }
+}
+
+/// Picks the language whose model best matches `text`, according to its monogram, trigram and
+/// word frequencies.
+///
+/// Every language has trigrams, so their scores can be compared. The word score is divided by the
+/// frequency of the most common word of the language, which puts it between 0 and 1: a text made
+/// only of that word is worth as much as trigrams ten times as likely.
+pub fn detect_language(text: &[u8]) -> Language {
+    Language::ALL
+        .into_iter()
+        .map(|language| {
+            let model = language.model();
+            let top_word = model.words.values().copied().fold(0.0, f64::max);
+            let score = model.trigrams.score(text)
+                + Bhattacharyya::new(language).score(text)
+                + WordFrequency::new(language).score(text) / top_word;
+            (language, score)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(language, _)| language)
+        .unwrap()
+}
+
+lazy_static! {
+    static ref ENGLISH: LanguageModel = LanguageModel {
+        language: Language::English,
+        monograms: &MONOGRAM_FREQ,
+        words: &WORD_FREQ,
+        bigrams: &BIGRAM_LOG_PROB,
+        trigrams: &TRIGRAM_LOG_PROB,
+        quadgrams: Some(&QUADGRAM_LOG_PROB),
+        bytes: &BYTE_FREQ,
+    };
+    static ref FRENCH: LanguageModel = LanguageModel {
+        language: Language::French,
+        monograms: &FRENCH_MONOGRAM_FREQ,
+        words: &FRENCH_WORD_FREQ,
+        bigrams: &FRENCH_NGRAMS[0],
+        trigrams: &FRENCH_NGRAMS[1],
+        quadgrams: None,
+        bytes: &FRENCH_BYTE_FREQ,
+    };
+    static ref GERMAN: LanguageModel = LanguageModel {
+        language: Language::German,
+        monograms: &GERMAN_MONOGRAM_FREQ,
+        words: &GERMAN_WORD_FREQ,
+        bigrams: &GERMAN_NGRAMS[0],
+        trigrams: &GERMAN_NGRAMS[1],
+        quadgrams: None,
+        bytes: &GERMAN_BYTE_FREQ,
+    };
+    static ref SPANISH: LanguageModel = LanguageModel {
+        language: Language::Spanish,
+        monograms: &SPANISH_MONOGRAM_FREQ,
+        words: &SPANISH_WORD_FREQ,
+        bigrams: &SPANISH_NGRAMS[0],
+        trigrams: &SPANISH_NGRAMS[1],
+        quadgrams: None,
+        bytes: &SPANISH_BYTE_FREQ,
+    };
+    static ref PORTUGUESE: LanguageModel = LanguageModel {
+        language: Language::Portuguese,
+        monograms: &PORTUGUESE_MONOGRAM_FREQ,
+        words: &PORTUGUESE_WORD_FREQ,
+        bigrams: &PORTUGUESE_NGRAMS[0],
+        trigrams: &PORTUGUESE_NGRAMS[1],
+        quadgrams: None,
+        bytes: &PORTUGUESE_BYTE_FREQ,
+    };
+    static ref FRENCH_NGRAMS: [NgramTable; 2] = [ngram_table!("fr", 2), ngram_table!("fr", 3)];
+    static ref GERMAN_NGRAMS: [NgramTable; 2] = [ngram_table!("de", 2), ngram_table!("de", 3)];
+    static ref SPANISH_NGRAMS: [NgramTable; 2] = [ngram_table!("es", 2), ngram_table!("es", 3)];
+    static ref PORTUGUESE_NGRAMS: [NgramTable; 2] = [ngram_table!("pt", 2), ngram_table!("pt", 3)];
+    static ref FRENCH_BYTE_FREQ: [f64; 256] = byte_table!("fr");
+    static ref GERMAN_BYTE_FREQ: [f64; 256] = byte_table!("de");
+    static ref SPANISH_BYTE_FREQ: [f64; 256] = byte_table!("es");
+    static ref PORTUGUESE_BYTE_FREQ: [f64; 256] = byte_table!("pt");
+    static ref FRENCH_MONOGRAM_FREQ: HashMap<char, f64> = HashMap::from_iter([
+        ('a', 0.07636),
+        ('b', 0.00901),
+        ('c', 0.0326),
+        ('d', 0.03669),
+        ('e', 0.14715),
+        ('f', 0.01066),
+        ('g', 0.00866),
+        ('h', 0.00737),
+        ('i', 0.07529),
+        ('j', 0.00613),
+        ('k', 0.00074),
+        ('l', 0.05456),
+        ('m', 0.02968),
+        ('n', 0.07095),
+        ('o', 0.05796),
+        ('p', 0.02521),
+        ('q', 0.01362),
+        ('r', 0.06693),
+        ('s', 0.07948),
+        ('t', 0.07244),
+        ('u', 0.06311),
+        ('v', 0.01838),
+        ('w', 0.00049),
+        ('x', 0.00427),
+        ('y', 0.00128),
+        ('z', 0.00326),
+        ('à', 0.00486),
+        ('â', 0.00051),
+        ('œ', 0.00018),
+        ('ç', 0.00085),
+        ('è', 0.00271),
+        ('é', 0.01504),
+        ('ê', 0.00218),
+        ('ë', 0.00008),
+        ('î', 0.00045),
+        ('ï', 0.00005),
+        ('ô', 0.00023),
+        ('ù', 0.00058),
+        ('û', 0.0006),
+    ]);
+    static ref GERMAN_MONOGRAM_FREQ: HashMap<char, f64> = HashMap::from_iter([
+        ('a', 0.06516),
+        ('b', 0.01886),
+        ('c', 0.02732),
+        ('d', 0.05076),
+        ('e', 0.16396),
+        ('f', 0.01656),
+        ('g', 0.03009),
+        ('h', 0.04577),
+        ('i', 0.0655),
+        ('j', 0.00268),
+        ('k', 0.01417),
+        ('l', 0.03437),
+        ('m', 0.02534),
+        ('n', 0.09776),
+        ('o', 0.02594),
+        ('p', 0.0067),
+        ('q', 0.00018),
+        ('r', 0.07003),
+        ('s', 0.0727),
+        ('t', 0.06154),
+        ('u', 0.04166),
+        ('v', 0.00846),
+        ('w', 0.01921),
+        ('x', 0.00034),
+        ('y', 0.00039),
+        ('z', 0.01134),
+        ('ä', 0.00578),
+        ('ö', 0.00443),
+        ('ß', 0.00307),
+        ('ü', 0.00995),
+    ]);
+    static ref SPANISH_MONOGRAM_FREQ: HashMap<char, f64> = HashMap::from_iter([
+        ('a', 0.11525),
+        ('b', 0.02215),
+        ('c', 0.04019),
+        ('d', 0.0501),
+        ('e', 0.12181),
+        ('f', 0.00692),
+        ('g', 0.01768),
+        ('h', 0.00703),
+        ('i', 0.06247),
+        ('j', 0.00493),
+        ('k', 0.00011),
+        ('l', 0.04967),
+        ('m', 0.03157),
+        ('n', 0.06712),
+        ('o', 0.08683),
+        ('p', 0.0251),
+        ('q', 0.00877),
+        ('r', 0.06871),
+        ('s', 0.07977),
+        ('t', 0.04632),
+        ('u', 0.02927),
+        ('v', 0.01138),
+        ('w', 0.00017),
+        ('x', 0.00215),
+        ('y', 0.01008),
+        ('z', 0.00467),
+        ('á', 0.00502),
+        ('é', 0.00433),
+        ('í', 0.00725),
+        ('ñ', 0.00311),
+        ('ó', 0.00827),
+        ('ú', 0.00168),
+        ('ü', 0.00012),
+    ]);
+    static ref PORTUGUESE_MONOGRAM_FREQ: HashMap<char, f64> = HashMap::from_iter([
+        ('a', 0.14634),
+        ('b', 0.01043),
+        ('c', 0.03882),
+        ('d', 0.04992),
+        ('e', 0.1257),
+        ('f', 0.01023),
+        ('g', 0.01303),
+        ('h', 0.00781),
+        ('i', 0.06186),
+        ('j', 0.00397),
+        ('k', 0.00015),
+        ('l', 0.02779),
+        ('m', 0.04738),
+        ('n', 0.04446),
+        ('o', 0.09735),
+        ('p', 0.02523),
+        ('q', 0.01204),
+        ('r', 0.0653),
+        ('s', 0.06805),
+        ('t', 0.04336),
+        ('u', 0.03639),
+        ('v', 0.01575),
+        ('w', 0.00037),
+        ('x', 0.00253),
+        ('y', 0.00006),
+        ('z', 0.0047),
+        ('à', 0.00072),
+        ('â', 0.00562),
+        ('á', 0.00118),
+        ('ã', 0.00733),
+        ('ç', 0.0053),
+        ('é', 0.00337),
+        ('ê', 0.0045),
+        ('í', 0.00132),
+        ('ô', 0.00635),
+        ('ó', 0.00296),
+        ('õ', 0.0004),
+        ('ú', 0.00207),
+        ('ü', 0.00026),
+    ]);
+    static ref FRENCH_WORD_FREQ: HashMap<&'static str, f64> = HashMap::from_iter([
+        ("de", 0.04),
+        ("la", 0.023),
+        ("le", 0.02),
+        ("et", 0.019),
+        ("les", 0.018),
+        ("des", 0.014),
+        ("en", 0.011),
+        ("à", 0.011),
+        ("un", 0.01),
+        ("du", 0.009),
+        ("une", 0.008),
+        ("que", 0.008),
+        ("est", 0.007),
+        ("pour", 0.006),
+        ("qui", 0.006),
+        ("dans", 0.006),
+        ("il", 0.005),
+        ("a", 0.005),
+        ("par", 0.005),
+        ("plus", 0.004),
+        ("pas", 0.004),
+        ("au", 0.004),
+        ("sur", 0.004),
+        ("ne", 0.003),
+        ("se", 0.003),
+        ("ce", 0.003),
+        ("avec", 0.003),
+        ("sont", 0.002),
+        ("son", 0.002),
+        ("elle", 0.002),
+    ]);
+    static ref GERMAN_WORD_FREQ: HashMap<&'static str, f64> = HashMap::from_iter([
+        ("der", 0.03),
+        ("die", 0.03),
+        ("und", 0.026),
+        ("in", 0.016),
+        ("den", 0.011),
+        ("von", 0.01),
+        ("zu", 0.009),
+        ("das", 0.009),
+        ("mit", 0.008),
+        ("sich", 0.007),
+        ("des", 0.007),
+        ("auf", 0.007),
+        ("sie", 0.006),
+        ("für", 0.006),
+        ("ist", 0.006),
+        ("im", 0.005),
+        ("dem", 0.005),
+        ("nicht", 0.005),
+        ("ein", 0.005),
+        ("eine", 0.004),
+        ("als", 0.004),
+        ("auch", 0.004),
+        ("es", 0.004),
+        ("an", 0.004),
+        ("er", 0.004),
+        ("hat", 0.003),
+        ("aus", 0.003),
+        ("bei", 0.003),
+        ("nach", 0.003),
+        ("wird", 0.003),
+    ]);
+    static ref SPANISH_WORD_FREQ: HashMap<&'static str, f64> = HashMap::from_iter([
+        ("de", 0.048),
+        ("la", 0.032),
+        ("que", 0.028),
+        ("el", 0.027),
+        ("en", 0.024),
+        ("y", 0.022),
+        ("a", 0.017),
+        ("los", 0.016),
+        ("del", 0.011),
+        ("se", 0.01),
+        ("las", 0.01),
+        ("por", 0.009),
+        ("un", 0.009),
+        ("para", 0.006),
+        ("con", 0.006),
+        ("no", 0.006),
+        ("una", 0.006),
+        ("su", 0.005),
+        ("al", 0.005),
+        ("lo", 0.004),
+        ("como", 0.004),
+        ("más", 0.003),
+        ("pero", 0.003),
+        ("sus", 0.003),
+        ("le", 0.003),
+        ("ya", 0.002),
+        ("o", 0.002),
+        ("este", 0.002),
+        ("sí", 0.002),
+        ("porque", 0.002),
+    ]);
+    static ref PORTUGUESE_WORD_FREQ: HashMap<&'static str, f64> = HashMap::from_iter([
+        ("de", 0.043),
+        ("a", 0.03),
+        ("o", 0.028),
+        ("que", 0.023),
+        ("e", 0.023),
+        ("do", 0.015),
+        ("da", 0.014),
+        ("em", 0.012),
+        ("um", 0.009),
+        ("para", 0.008),
+        ("é", 0.007),
+        ("com", 0.007),
+        ("não", 0.007),
+        ("uma", 0.006),
+        ("os", 0.006),
+        ("no", 0.006),
+        ("se", 0.006),
+        ("na", 0.005),
+        ("por", 0.005),
+        ("mais", 0.004),
+        ("as", 0.004),
+        ("dos", 0.004),
+        ("como", 0.004),
+        ("mas", 0.003),
+        ("ao", 0.003),
+        ("das", 0.003),
+        ("foi", 0.002),
+        ("ele", 0.002),
+        ("tem", 0.002),
+        ("à", 0.002),
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monograms_sum_to_one() {
+        for language in Language::ALL {
+            let total = language.model().monograms.values().sum::<f64>();
+            assert!((total - 1.0).abs() < 0.01, "{:?}: {}", language, total);
+        }
+    }
+
+    #[test]
+    fn test_ngrams() {
+        assert_eq!(4, Language::English.model().ngrams().n());
+        for language in &Language::ALL[1..] {
+            assert!(language.model().quadgrams.is_none());
+            assert_eq!(3, language.model().ngrams().n());
+        }
+    }
+
+    #[test]
+    fn test_detect_language() {
+        let samples = [
+            (
+                Language::English,
+                "The quick brown fox jumps over the lazy dog near the river.",
+            ),
+            (
+                Language::French,
+                "Le renard brun saute par-dessus le chien paresseux près de la rivière.",
+            ),
+            (
+                Language::German,
+                "Der schnelle braune Fuchs springt über den faulen Hund am Fluss.",
+            ),
+            (
+                Language::Spanish,
+                "El rápido zorro marrón salta sobre el perro perezoso junto al río.",
+            ),
+            (
+                Language::Portuguese,
+                "A rápida raposa marrom salta sobre o cão preguiçoso perto do rio.",
+            ),
+        ];
+
+        for (language, text) in samples {
+            assert_eq!(language, detect_language(text.as_bytes()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_bhattacharyya_distance() {
+        let french = Language::French.model();
+        assert!(french.bhattacharyya_distance(french.monograms).abs() < 0.01);
+        assert!(
+            french.bhattacharyya_distance(Language::English.model().monograms)
+                < french.bhattacharyya_distance(&HashMap::from_iter([('z', 1.0)]))
+        );
+    }
+}
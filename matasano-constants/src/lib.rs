@@ -1,5 +1,5 @@
-//! Frequencies generated from around 4.5 billion characters of English
-//! text ([source]).
+//! English frequencies generated from around 4.5 billion characters of English
+//! text ([source]). Models for other languages live in [`language`].
 //!
 //! [source]: <http://practicalcryptography.com/cryptanalysis/letter-frequencies-various-languages/english-letter-frequencies/>
 
@@ -7,6 +7,15 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 
+pub mod byte_freq;
+mod fold;
+pub mod language;
+pub mod metrics;
+pub mod model;
+pub mod ngram;
+pub mod score;
+pub mod trie;
+
 lazy_static! {
     /// Frequency of English single letters
     pub static ref MONOGRAM_FREQ: HashMap<char, f64> = HashMap::from_iter([
@@ -38,6 +47,14 @@ lazy_static! {
         ('z', 0.0011),
     ]);
 
+    /// Around 29,000 English words from the vocabulary of the [Snowball] stemmer, lowercased.
+    ///
+    /// [Snowball]: <https://snowballstem.org/>
+    pub static ref ENGLISH_WORDS: trie::Trie = trie::Trie::from_le_bytes(include_bytes!(concat!(
+        env!("OUT_DIR"),
+        "/en_words.bin"
+    )));
+
     /// Most common words in a 'news' text corpus containing around 900 million words.
     pub static ref WORD_FREQ: HashMap<&'static str, f64> = HashMap::from_iter([
         ("the", 0.0642),
@@ -74,16 +91,17 @@ lazy_static! {
 }
 
 /// Calculates the similarity between two probability distributions. The lower the value, the more
-/// similar they are. More info [`here`].
+/// similar they are. Letters missing from either distribution have probability zero, so any
+/// language model can be used (see [`language::LanguageModel::bhattacharyya_distance`]). The
+/// distance is infinite when the distributions have no letter in common; [`metrics`] has bounded
+/// alternatives. More info [`here`].
 ///
 /// [`here`]: <https://en.wikipedia.org/wiki/Bhattacharyya_distance>
 pub fn bhattacharyya_distance(a: &HashMap<char, f64>, b: &HashMap<char, f64>) -> f64 {
     let mut bc = 0_f64;
 
-    for letter in MONOGRAM_FREQ.keys() {
-        bc += f64::sqrt(
-            a.get(letter).copied().unwrap_or_default() * b.get(letter).copied().unwrap_or_default(),
-        );
+    for (letter, p) in a {
+        bc += f64::sqrt(p * b.get(letter).copied().unwrap_or_default());
     }
 
     -bc.ln()
@@ -0,0 +1,378 @@
+//! Distances and similarities between probability distributions.
+//!
+//! [`crate::bhattacharyya_distance`] is infinite as soon as two distributions have no outcome in
+//! common, which makes it useless to tell apart two candidates that are both far from English.
+//! Jensen-Shannon divergence, total variation, Hellinger distance and cosine similarity are
+//! bounded and always defined. Each metric documents how it treats outcomes with probability
+//! zero. An outcome that is missing from a [`Distribution`] has probability zero.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// A discrete probability distribution over outcomes of type `K`, typically letters or bytes.
+///
+/// Probabilities aren't normalised on construction, so that partial tables (such as the most
+/// common words of a language) can be compared as they are.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Distribution<K: Eq + Hash = char> {
+    probs: HashMap<K, f64>,
+}
+
+impl<K: Eq + Hash> Distribution<K> {
+    /// Relative frequency of every outcome of `outcomes`. Empty if there are no outcomes.
+    pub fn from_outcomes(outcomes: impl IntoIterator<Item = K>) -> Self {
+        let mut probs = HashMap::new();
+        let mut total = 0;
+        for outcome in outcomes {
+            *probs.entry(outcome).or_insert(0_f64) += 1.0;
+            total += 1;
+        }
+
+        for p in probs.values_mut() {
+            *p /= f64::from(total);
+        }
+
+        Distribution { probs }
+    }
+
+    /// Probability of `outcome`, zero if it's missing.
+    pub fn get(&self, outcome: &K) -> f64 {
+        self.probs.get(outcome).copied().unwrap_or_default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.probs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.probs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, f64)> + '_ {
+        self.probs.iter().map(|(k, p)| (k, *p))
+    }
+
+    /// Sum of the probabilities, which is 1 unless the distribution is partial or empty.
+    pub fn total(&self) -> f64 {
+        self.probs.values().sum()
+    }
+
+    /// Calls `f` with the probabilities of every outcome that appears in either distribution.
+    fn zip_with(&self, other: &Self, mut f: impl FnMut(f64, f64)) {
+        for (k, p) in &self.probs {
+            f(*p, other.get(k));
+        }
+        for (k, q) in &other.probs {
+            if !self.probs.contains_key(k) {
+                f(0.0, *q);
+            }
+        }
+    }
+}
+
+impl Distribution<char> {
+    /// Distribution of the lowercased characters of `bytes`, read as lossy UTF-8.
+    pub fn from_text(bytes: &[u8]) -> Self {
+        Distribution::from_outcomes(
+            String::from_utf8_lossy(bytes)
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c)),
+        )
+    }
+}
+
+impl Distribution<u8> {
+    /// Distribution of the byte values of `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Distribution::from_outcomes(bytes.iter().copied())
+    }
+}
+
+impl<K: Eq + Hash> FromIterator<(K, f64)> for Distribution<K> {
+    fn from_iter<T: IntoIterator<Item = (K, f64)>>(iter: T) -> Self {
+        Distribution {
+            probs: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone> From<&HashMap<K, f64>> for Distribution<K> {
+    fn from(map: &HashMap<K, f64>) -> Self {
+        Distribution { probs: map.clone() }
+    }
+}
+
+impl From<&[f64; 256]> for Distribution<u8> {
+    fn from(table: &[f64; 256]) -> Self {
+        (0..=u8::MAX)
+            .zip(table.iter().copied())
+            .filter(|(_, p)| *p > 0.0)
+            .collect()
+    }
+}
+
+/// Pearson's chi-squared divergence, the sum of `(p - q)² / q`. Not symmetric. Outcomes where
+/// both probabilities are zero are skipped, and an outcome that is observed but not expected
+/// makes the divergence infinite.
+pub fn chi_squared<K: Eq + Hash>(observed: &Distribution<K>, expected: &Distribution<K>) -> f64 {
+    let mut res = 0_f64;
+    observed.zip_with(expected, |p, q| {
+        if q > 0.0 {
+            res += (p - q).powi(2) / q;
+        } else if p > 0.0 {
+            res = f64::INFINITY;
+        }
+    });
+    res
+}
+
+/// Kullback-Leibler divergence of `q` from `p`, in nats. Not symmetric. Outcomes with `p` zero
+/// contribute nothing, and an outcome with `p` positive but `q` zero makes the divergence
+/// infinite.
+pub fn kullback_leibler<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    let mut res = 0_f64;
+    p.zip_with(q, |p, q| {
+        if p > 0.0 {
+            res += p * (p / q).ln();
+        }
+    });
+    res
+}
+
+/// Jensen-Shannon divergence, in nats: the average Kullback-Leibler divergence of both
+/// distributions from their mixture. Symmetric and always between 0 and `ln 2`, since the mixture
+/// is never zero where either distribution isn't.
+pub fn jensen_shannon<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    let mut res = 0_f64;
+    p.zip_with(q, |p, q| {
+        let m = (p + q) / 2.0;
+        for x in [p, q] {
+            if x > 0.0 {
+                res += x * (x / m).ln() / 2.0;
+            }
+        }
+    });
+    res
+}
+
+/// Total variation distance, half the sum of `|p - q|`. Zero probabilities need no special
+/// treatment and the result is between 0 and 1.
+pub fn total_variation<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    let mut res = 0_f64;
+    p.zip_with(q, |p, q| res += (p - q).abs());
+    res / 2.0
+}
+
+/// Hellinger distance, `sqrt(1 - BC)` where `BC` is the Bhattacharyya coefficient. Zero
+/// probabilities need no special treatment and the result is between 0 and 1, where
+/// [`bhattacharyya`] would be infinite.
+pub fn hellinger<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    (1.0 - bhattacharyya_coefficient(p, q)).max(0.0).sqrt()
+}
+
+/// Bhattacharyya distance, `-ln(BC)`. Infinite if the distributions have no outcome in common.
+/// Same as [`crate::bhattacharyya_distance`].
+pub fn bhattacharyya<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    -bhattacharyya_coefficient(p, q).ln()
+}
+
+fn bhattacharyya_coefficient<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    p.iter().map(|(k, p)| f64::sqrt(p * q.get(k))).sum()
+}
+
+/// Cosine similarity of the distributions seen as vectors. Unlike the other metrics, the higher
+/// the value the more similar they are. Between 0 and 1, and 0 if either distribution is empty.
+pub fn cosine_similarity<K: Eq + Hash>(p: &Distribution<K>, q: &Distribution<K>) -> f64 {
+    let dot = p.iter().map(|(k, p)| p * q.get(k)).sum::<f64>();
+    let norm = |d: &Distribution<K>| d.iter().map(|(_, p)| p * p).sum::<f64>().sqrt();
+    let norms = norm(p) * norm(q);
+
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// Every metric of this module, so that they can be compared or picked at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    ChiSquared,
+    KullbackLeibler,
+    JensenShannon,
+    TotalVariation,
+    Hellinger,
+    Bhattacharyya,
+    Cosine,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 7] = [
+        Metric::ChiSquared,
+        Metric::KullbackLeibler,
+        Metric::JensenShannon,
+        Metric::TotalVariation,
+        Metric::Hellinger,
+        Metric::Bhattacharyya,
+        Metric::Cosine,
+    ];
+
+    /// Distance between `observed` and `expected`: the lower, the more similar. Cosine similarity
+    /// is turned into a distance by subtracting it from 1.
+    pub fn distance<K: Eq + Hash>(
+        self,
+        observed: &Distribution<K>,
+        expected: &Distribution<K>,
+    ) -> f64 {
+        match self {
+            Metric::ChiSquared => chi_squared(observed, expected),
+            Metric::KullbackLeibler => kullback_leibler(observed, expected),
+            Metric::JensenShannon => jensen_shannon(observed, expected),
+            Metric::TotalVariation => total_variation(observed, expected),
+            Metric::Hellinger => hellinger(observed, expected),
+            Metric::Bhattacharyya => bhattacharyya(observed, expected),
+            Metric::Cosine => 1.0 - cosine_similarity(observed, expected),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{byte_freq::BYTE_FREQ, MONOGRAM_FREQ};
+
+    fn dist(probs: &[(char, f64)]) -> Distribution {
+        probs.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_identical() {
+        let english = Distribution::from_text(b"Cooking MC's like a pound of bacon");
+        for metric in Metric::ALL {
+            assert!(
+                metric.distance(&english, &english).abs() < 1e-9,
+                "{:?}",
+                metric
+            );
+        }
+    }
+
+    #[test]
+    fn test_disjoint() {
+        let a = dist(&[('a', 0.5), ('b', 0.5)]);
+        let c = dist(&[('c', 1.0)]);
+
+        assert_eq!(f64::INFINITY, chi_squared(&a, &c));
+        assert_eq!(f64::INFINITY, kullback_leibler(&a, &c));
+        assert_eq!(f64::INFINITY, bhattacharyya(&a, &c));
+        assert!((jensen_shannon(&a, &c) - 2_f64.ln()).abs() < 1e-12);
+        assert_eq!(1.0, total_variation(&a, &c));
+        assert_eq!(1.0, hellinger(&a, &c));
+        assert_eq!(0.0, cosine_similarity(&a, &c));
+    }
+
+    #[test]
+    fn test_values() {
+        let p = dist(&[('a', 0.5), ('b', 0.5)]);
+        let q = dist(&[('a', 0.75), ('b', 0.25)]);
+
+        assert!((chi_squared(&p, &q) - (0.0625 / 0.75 + 0.0625 / 0.25)).abs() < 1e-12);
+        assert!(
+            (kullback_leibler(&p, &q) - 0.5 * (2.0 / 3.0_f64).ln() - 0.5 * 2_f64.ln()).abs()
+                < 1e-12
+        );
+        assert!((total_variation(&p, &q) - 0.25).abs() < 1e-12);
+        assert!((jensen_shannon(&p, &q) - jensen_shannon(&q, &p)).abs() < 1e-12);
+        assert_eq!(
+            bhattacharyya(&p, &q),
+            crate::bhattacharyya_distance(&p.probs, &q.probs)
+        );
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let observed = Distribution::from_bytes(b"aab");
+        assert!((observed.get(&b'a') - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(0.0, observed.get(&b'c'));
+        assert!((Distribution::from(&*BYTE_FREQ).total() - 1.0).abs() < 1e-9);
+        assert!(Distribution::from_text(b"").is_empty());
+        assert_eq!(26, Distribution::from(&*MONOGRAM_FREQ).len());
+    }
+
+    /// Decodes every non-blank line of `text` from hex.
+    fn hex_lines(text: &str) -> Vec<Vec<u8>> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                (0..line.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(&line[i..i + 2], 16).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Rank under every metric of [`Metric::ALL`], 0 being the first, of the decryption of
+    /// `lines[answer.0]` with the single-byte key `answer.1`, among the decryptions of every line
+    /// with every key sorted by distance to the English byte frequencies.
+    fn ranks(lines: &[Vec<u8>], answer: (usize, u8)) -> [usize; 7] {
+        let english = Distribution::from(&*BYTE_FREQ);
+        let candidates = lines
+            .iter()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                (0..=u8::MAX).map(move |key| {
+                    let plaintext = line.iter().map(|b| b ^ key).collect::<Vec<_>>();
+                    ((i, key), Distribution::from_bytes(&plaintext))
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Metric::ALL.map(|metric| {
+            let distances = candidates
+                .iter()
+                .map(|(candidate, observed)| (*candidate, metric.distance(observed, &english)))
+                .collect::<Vec<_>>();
+            let (_, target) = distances.iter().find(|(c, _)| *c == answer).unwrap();
+            distances.iter().filter(|(_, d)| d < target).count()
+        })
+    }
+
+    /// Number of `len` byte chunks of English text, each XORed with a different single-byte key,
+    /// whose decryption every metric ranks first.
+    fn solved_chunks(len: usize) -> ([usize; 7], usize) {
+        let chunks = include_bytes!("../../testdata/english.txt").chunks_exact(len);
+        let total = chunks.len();
+        let mut solved = [0; 7];
+
+        for (i, chunk) in chunks.enumerate() {
+            let key = (i * 37 + 11) as u8;
+            let ciphertext = chunk.iter().map(|b| b ^ key).collect::<Vec<_>>();
+            for (n, rank) in solved.iter_mut().zip(ranks(&[ciphertext], (0, key))) {
+                *n += usize::from(rank == 0);
+            }
+        }
+
+        (solved, total)
+    }
+
+    #[test]
+    fn test_ranking_accuracy() {
+        // Every metric finds the answers of challenges 3 and 4 among all the decryptions, even
+        // the unprintable ones.
+        let challenge3 =
+            hex_lines("1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736");
+        let challenge4 = hex_lines(include_str!("../../matasano-challenges/data/4.txt"));
+        assert_eq!([0; 7], ranks(&challenge3, (0, 88)));
+        assert_eq!([0; 7], ranks(&challenge4, (170, 53)));
+
+        // Five bytes are too few for any of them to be always right, which tells them apart:
+        // cosine similarity is clearly the worst, the others solve about nine chunks out of ten.
+        let (solved, total) = solved_chunks(5);
+        let cosine = solved[6];
+        for (metric, n) in Metric::ALL.iter().zip(solved).take(6) {
+            assert!(n * 100 > total * 85, "{:?}: {}/{}", metric, n, total);
+            assert!(n > cosine + total / 20, "{:?}: {} vs {}", metric, n, cosine);
+        }
+    }
+}
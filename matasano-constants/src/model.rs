@@ -0,0 +1,475 @@
+//! Frequency models trained from an arbitrary corpus, so that scoring can be tuned to whatever
+//! the plaintexts are expected to look like (log files, JSON, source code...).
+//!
+//! A model can be saved with [`FrequencyModel::write_to`] and loaded back at runtime with
+//! [`FrequencyModel::read_from`], or embedded at compile time with [`FrequencyModel::from_bytes`].
+//! `data/models/json.model` is one, trained on JSON records:
+//!
+//! ```
+//! use matasano_constants::{model::FrequencyModel, score::Scorer};
+//!
+//! let model = FrequencyModel::from_bytes(include_bytes!(concat!(
+//!     env!("CARGO_MANIFEST_DIR"),
+//!     "/data/models/json.model"
+//! )))?;
+//! assert!(model.score(br#"{"id": 9}"#) > model.score(b"Dear Sir"));
+//! # Ok::<(), matasano_constants::model::ParseModelError>(())
+//! ```
+//!
+//! The serialized format is line oriented text. Every line is a record made of a tag followed
+//! by space separated fields:
+//!
+//! ```text
+//! byte <hex value> <probability>
+//! mono <letter> <probability>
+//! unseen-mono <probability>
+//! floor <n> <log10 probability>
+//! ngram <letters> <log10 probability>
+//! word <word> <probability>
+//! unseen-word <probability>
+//! ```
+//!
+//! Only the n-grams above their floor are written. Empty lines and lines starting with `#` are
+//! ignored.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+use crate::{byte_freq::byte_log_likelihood, fold::fold_letters, ngram::NgramTable, score::Scorer};
+
+/// How probability mass is assigned to events that never appear in the training corpus.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Smoothing {
+    /// Maximum likelihood estimates. Unseen n-grams get a floor of `0.01 / N` and other unseen
+    /// events get probability zero.
+    None,
+    /// Adds `k` to the count of every event, seen or not.
+    AddK(f64),
+    /// Simple Good-Turing: an event seen `r` times is counted `(r + 1) * N(r + 1) / N(r)` times,
+    /// where `N(r)` is the number of events seen `r` times, and unseen events share `N(1) / N`.
+    /// Counts for which `N(r + 1)` is zero are left alone.
+    GoodTuring,
+}
+
+/// Smooths the counts of a vocabulary. Returns the probability of each entry, along with the
+/// probability of an entry that was never seen.
+fn smooth(counts: &[u64], smoothing: Smoothing) -> (Vec<f64>, f64) {
+    let total = counts.iter().sum::<u64>() as f64;
+    if total == 0.0 {
+        let p = 1.0 / counts.len().max(1) as f64;
+        return (vec![p; counts.len()], p);
+    }
+
+    match smoothing {
+        Smoothing::None => (counts.iter().map(|c| *c as f64 / total).collect(), 0.0),
+        Smoothing::AddK(k) => {
+            let denominator = total + k * counts.len() as f64;
+            (
+                counts
+                    .iter()
+                    .map(|c| (*c as f64 + k) / denominator)
+                    .collect(),
+                k / denominator,
+            )
+        }
+        Smoothing::GoodTuring => {
+            let mut n_r = HashMap::<u64, u64>::new();
+            for c in counts {
+                *n_r.entry(*c).or_default() += 1;
+            }
+
+            let unseen_types = n_r.get(&0).copied().unwrap_or_default();
+            let unseen_mass = if unseen_types == 0 {
+                0.0
+            } else {
+                n_r.get(&1).copied().unwrap_or_default() as f64 / total
+            };
+
+            let adjusted = counts
+                .iter()
+                .map(|r| match (n_r.get(r), n_r.get(&(r + 1))) {
+                    (_, _) if *r == 0 => 0.0,
+                    (Some(nr), Some(nr1)) => (r + 1) as f64 * *nr1 as f64 / *nr as f64,
+                    _ => *r as f64,
+                })
+                .collect::<Vec<_>>();
+
+            let seen_total = adjusted.iter().sum::<f64>();
+            let unseen = if unseen_types == 0 {
+                0.0
+            } else {
+                unseen_mass / unseen_types as f64
+            };
+
+            let probs = counts
+                .iter()
+                .zip(adjusted)
+                .map(|(r, a)| {
+                    if *r == 0 {
+                        unseen
+                    } else {
+                        a / seen_total * (1.0 - unseen_mass)
+                    }
+                })
+                .collect();
+
+            (probs, unseen)
+        }
+    }
+}
+
+/// Smooths the counts of an open vocabulary, such as words, where unseen entries are counted as
+/// one extra type.
+fn smooth_map<K: Clone + Eq + std::hash::Hash>(
+    counts: &HashMap<K, u64>,
+    smoothing: Smoothing,
+) -> (HashMap<K, f64>, f64) {
+    let keys = counts.keys().cloned().collect::<Vec<_>>();
+    let mut values = keys.iter().map(|k| counts[k]).collect::<Vec<_>>();
+    values.push(0);
+
+    let (probs, unseen) = smooth(&values, smoothing);
+    (keys.into_iter().zip(probs).collect(), unseen)
+}
+
+fn ngram_table(letters: &[u8], n: usize, smoothing: Smoothing) -> NgramTable {
+    let mut counts = vec![0_u64; 26_usize.pow(n as u32)];
+    for window in letters.windows(n) {
+        counts[window
+            .iter()
+            .fold(0, |acc, c| acc * 26 + (c - b'a') as usize)] += 1;
+    }
+
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    let (probs, unseen) = smooth(&counts, smoothing);
+    let floor = if unseen > 0.0 {
+        unseen.log10()
+    } else {
+        (0.01 / total).log10()
+    } as f32;
+
+    let log_probs = probs
+        .iter()
+        .map(|p| if *p > 0.0 { p.log10() as f32 } else { floor })
+        .collect();
+
+    NgramTable::from_log_probs(n, floor, log_probs)
+}
+
+/// Monogram, n-gram, byte and word distributions of a corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrequencyModel {
+    /// Probability of every byte value.
+    pub bytes: [f64; 256],
+    /// Probability of every lowercase letter, accented ones included.
+    pub monograms: HashMap<char, f64>,
+    /// Probability of a letter that doesn't appear in `monograms`.
+    pub unseen_monogram: f64,
+    pub bigrams: NgramTable,
+    pub trigrams: NgramTable,
+    pub quadgrams: NgramTable,
+    /// Probability of every lowercase word, without leading or trailing punctuation.
+    pub words: HashMap<String, f64>,
+    /// Probability of a word that doesn't appear in `words`.
+    pub unseen_word: f64,
+}
+
+impl FrequencyModel {
+    /// Reads the whole of `corpus` and computes its distributions. Invalid UTF-8 is replaced
+    /// before counting letters and words, but bytes are counted as they are.
+    pub fn train(mut corpus: impl Read, smoothing: Smoothing) -> io::Result<Self> {
+        let mut raw = Vec::new();
+        corpus.read_to_end(&mut raw)?;
+        let text = String::from_utf8_lossy(&raw);
+
+        let mut byte_counts = [0_u64; 256];
+        for byte in &raw {
+            byte_counts[*byte as usize] += 1;
+        }
+        let (byte_probs, _) = smooth(&byte_counts, smoothing);
+        let mut bytes = [0_f64; 256];
+        bytes.copy_from_slice(&byte_probs);
+
+        let mut monogram_counts = HashMap::new();
+        for c in text.chars().filter(|c| c.is_alphabetic()) {
+            *monogram_counts
+                .entry(c.to_lowercase().next().unwrap_or(c))
+                .or_default() += 1;
+        }
+        let (monograms, unseen_monogram) = smooth_map(&monogram_counts, smoothing);
+
+        let mut word_counts = HashMap::new();
+        for word in text.to_lowercase().split_whitespace() {
+            let word = word.trim_matches(|c: char| c.is_ascii_punctuation());
+            if !word.is_empty() {
+                *word_counts.entry(word.to_string()).or_default() += 1;
+            }
+        }
+        let (words, unseen_word) = smooth_map(&word_counts, smoothing);
+
+        let letters = fold_letters(&raw);
+
+        Ok(FrequencyModel {
+            bytes,
+            monograms,
+            unseen_monogram,
+            bigrams: ngram_table(&letters, 2, smoothing),
+            trigrams: ngram_table(&letters, 3, smoothing),
+            quadgrams: ngram_table(&letters, 4, smoothing),
+            words,
+            unseen_word,
+        })
+    }
+
+    /// Writes the model in the text format described in the module documentation.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+
+    /// Parses a model in the text format described in the module documentation, typically one
+    /// embedded with `include_bytes!`. Invalid UTF-8 is an error at the line it's on.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseModelError> {
+        std::str::from_utf8(bytes)
+            .map_err(|e| {
+                let line = bytes[..e.valid_up_to()]
+                    .iter()
+                    .filter(|b| **b == b'\n')
+                    .count();
+                ParseModelError(line + 1)
+            })?
+            .parse()
+    }
+
+    /// Reads a model in the text format described in the module documentation.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        text.parse()
+            .map_err(|e: ParseModelError| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Scores candidate plaintexts by their average log-likelihood per byte under the model, which
+/// works for any kind of plaintext, not only natural language.
+impl Scorer for FrequencyModel {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        byte_log_likelihood(bytes, &self.bytes)
+    }
+}
+
+impl fmt::Display for FrequencyModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# matasano frequency model")?;
+
+        for (byte, p) in self.bytes.iter().enumerate() {
+            writeln!(f, "byte {:02x} {}", byte, p)?;
+        }
+
+        let mut monograms = self.monograms.iter().collect::<Vec<_>>();
+        monograms.sort_by(|a, b| a.0.cmp(b.0));
+        for (c, p) in monograms {
+            writeln!(f, "mono {} {}", c, p)?;
+        }
+        writeln!(f, "unseen-mono {}", self.unseen_monogram)?;
+
+        for table in [&self.bigrams, &self.trigrams, &self.quadgrams] {
+            writeln!(f, "floor {} {}", table.n(), table.floor())?;
+            for (ngram, p) in table.iter() {
+                writeln!(f, "ngram {} {}", String::from_utf8_lossy(&ngram), p)?;
+            }
+        }
+
+        let mut words = self.words.iter().collect::<Vec<_>>();
+        words.sort_by(|a, b| a.0.cmp(b.0));
+        for (word, p) in words {
+            writeln!(f, "word {} {}", word, p)?;
+        }
+        writeln!(f, "unseen-word {}", self.unseen_word)
+    }
+}
+
+/// Error returned when a serialized [`FrequencyModel`] is malformed. Holds the offending line
+/// number, starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModelError(pub usize);
+
+impl fmt::Display for ParseModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed frequency model at line {}", self.0)
+    }
+}
+
+impl std::error::Error for ParseModelError {}
+
+impl FromStr for FrequencyModel {
+    type Err = ParseModelError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0_f64; 256];
+        let mut monograms = HashMap::new();
+        let mut unseen_monogram = 0.0;
+        let mut floors = [0_f32; 3];
+        let mut ngrams = [
+            vec![None; 26_usize.pow(2)],
+            vec![None; 26_usize.pow(3)],
+            vec![None; 26_usize.pow(4)],
+        ];
+        let mut words = HashMap::new();
+        let mut unseen_word = 0.0;
+
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let err = || ParseModelError(i + 1);
+            let fields = line.split(' ').collect::<Vec<_>>();
+
+            match fields.as_slice() {
+                ["byte", byte, p] => {
+                    let byte = u8::from_str_radix(byte, 16).map_err(|_| err())?;
+                    bytes[byte as usize] = p.parse().map_err(|_| err())?;
+                }
+                ["mono", c, p] => {
+                    let mut chars = c.chars();
+                    let c = chars
+                        .next()
+                        .filter(|_| chars.next().is_none())
+                        .ok_or_else(err)?;
+                    monograms.insert(c, p.parse().map_err(|_| err())?);
+                }
+                ["unseen-mono", p] => unseen_monogram = p.parse().map_err(|_| err())?,
+                ["floor", n, p] => {
+                    let n = n.parse::<usize>().map_err(|_| err())?;
+                    if !(2..=4).contains(&n) {
+                        return Err(err());
+                    }
+                    floors[n - 2] = p.parse().map_err(|_| err())?;
+                }
+                ["ngram", ngram, p] => {
+                    if !(2..=4).contains(&ngram.len())
+                        || !ngram.bytes().all(|c| c.is_ascii_lowercase())
+                    {
+                        return Err(err());
+                    }
+                    let index = ngram
+                        .bytes()
+                        .fold(0, |acc, c| acc * 26 + (c - b'a') as usize);
+                    ngrams[ngram.len() - 2][index] = Some(p.parse().map_err(|_| err())?);
+                }
+                ["word", word, p] => {
+                    words.insert(word.to_string(), p.parse().map_err(|_| err())?);
+                }
+                ["unseen-word", p] => unseen_word = p.parse().map_err(|_| err())?,
+                _ => return Err(err()),
+            }
+        }
+
+        let [bigrams, trigrams, quadgrams] = [0, 1, 2].map(|i| {
+            NgramTable::from_log_probs(
+                i + 2,
+                floors[i],
+                ngrams[i].iter().map(|p| p.unwrap_or(floors[i])).collect(),
+            )
+        });
+
+        Ok(FrequencyModel {
+            bytes,
+            monograms,
+            unseen_monogram,
+            bigrams,
+            trigrams,
+            quadgrams,
+            words,
+            unseen_word,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPUS: &str = "{\"id\": 1, \"name\": \"alice\", \"tags\": [\"admin\", \
+                          \"user\"]}\n{\"id\": 2, \"name\": \"bob\", \"tags\": \
+                          [\"user\"]}\n{\"id\": 3, \"name\": \"carol\", \"tags\": []}\n";
+
+    #[test]
+    fn test_smoothing_sums_to_one() {
+        for smoothing in [Smoothing::None, Smoothing::AddK(0.5), Smoothing::GoodTuring] {
+            let model = FrequencyModel::train(CORPUS.as_bytes(), smoothing).unwrap();
+            assert!((model.bytes.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+            let unseen_bytes = model.bytes.iter().filter(|p| **p == 0.0).count();
+            assert_eq!(smoothing == Smoothing::None, unseen_bytes > 0);
+
+            let words = model.words.values().sum::<f64>();
+            let unseen = if smoothing == Smoothing::None {
+                0.0
+            } else {
+                model.unseen_word
+            };
+            assert!((words + unseen - 1.0).abs() < 1e-9, "{:?}", smoothing);
+        }
+    }
+
+    #[test]
+    fn test_good_turing() {
+        let (probs, unseen) = smooth(&[0, 1, 1, 2, 3], Smoothing::GoodTuring);
+        // N(1) = 2 out of 7 tokens goes to the single unseen type.
+        assert!((unseen - 2.0 / 7.0).abs() < 1e-12);
+        assert_eq!(probs[1], probs[2]);
+        assert!((probs.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let model = FrequencyModel::train(CORPUS.as_bytes(), Smoothing::AddK(1.0)).unwrap();
+        let mut serialized = Vec::new();
+        model.write_to(&mut serialized).unwrap();
+
+        assert_eq!(
+            model,
+            FrequencyModel::read_from(serialized.as_slice()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert_eq!(
+            Err(ParseModelError(2)),
+            "byte 00 0.5\nbyte zz 0.5".parse::<FrequencyModel>()
+        );
+    }
+
+    #[test]
+    fn test_embedded_model() {
+        let model =
+            FrequencyModel::from_bytes(include_bytes!("../data/models/json.model")).unwrap();
+
+        assert!((model.bytes.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(model.words.contains_key("email"));
+        assert!(
+            model.score(b"{\"id\": 9, \"name\": \"ivan\"}")
+                > model.score(b"Dear Sir, I write to you today")
+        );
+        assert_eq!(
+            Err(ParseModelError(2)),
+            FrequencyModel::from_bytes(b"byte 00 0.5\nbyte \xff 0.5")
+        );
+    }
+
+    #[test]
+    fn test_domain_scoring() {
+        let model = FrequencyModel::train(CORPUS.as_bytes(), Smoothing::AddK(0.1)).unwrap();
+        assert!(
+            model.score(b"{\"id\": 4, \"name\": \"dave\"}")
+                > model.score(b"Dear Sir, I write to you today")
+        );
+    }
+}
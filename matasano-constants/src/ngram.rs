@@ -0,0 +1,218 @@
+//! English bigram, trigram and quadgram log-probabilities, generated at build time from the
+//! corpus in `data/corpus` (a small collection of English prose, mostly excerpts of public domain
+//! works). Letters are case insensitive, accented letters are folded to their unaccented
+//! equivalent and every other character of the corpus is dropped, so n-grams span word
+//! boundaries. Tables for other languages are available through [`crate::language`].
+
+use lazy_static::lazy_static;
+
+use crate::{
+    fold::{fold_letter, fold_letters},
+    score::Scorer,
+};
+
+/// Decodes the table for language `$lang` and n-gram length `$n` generated by the build script.
+macro_rules! ngram_table {
+    ($lang:literal, $n:literal) => {
+        $crate::ngram::NgramTable::from_le_bytes(
+            $n,
+            include_bytes!(concat!(env!("OUT_DIR"), "/", $lang, "_", $n, "grams.bin")),
+        )
+    };
+}
+
+pub(crate) use ngram_table;
+
+lazy_static! {
+    /// Base 10 log-probabilities of English bigrams.
+    pub static ref BIGRAM_LOG_PROB: NgramTable = ngram_table!("en", 2);
+
+    /// Base 10 log-probabilities of English trigrams.
+    pub static ref TRIGRAM_LOG_PROB: NgramTable = ngram_table!("en", 3);
+
+    /// Base 10 log-probabilities of English quadgrams.
+    pub static ref QUADGRAM_LOG_PROB: NgramTable = ngram_table!("en", 4);
+}
+
+/// Log-probabilities of every sequence of `n` letters, stored in a flat array indexed by the
+/// letters of the n-gram in base 26.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NgramTable {
+    n: usize,
+    floor: f64,
+    log_probs: Vec<f32>,
+}
+
+impl NgramTable {
+    /// Decodes a table produced by the build script: the floor followed by `26^n`
+    /// log-probabilities, all of them little-endian `f32`s.
+    pub(crate) fn from_le_bytes(n: usize, bytes: &[u8]) -> Self {
+        let mut values = bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()));
+        let floor = values.next().unwrap();
+        NgramTable::from_log_probs(n, floor, values.collect())
+    }
+
+    /// Builds a table out of the floor and `26^n` log-probabilities.
+    pub(crate) fn from_log_probs(n: usize, floor: f32, log_probs: Vec<f32>) -> Self {
+        assert_eq!(26_usize.pow(n as u32), log_probs.len());
+
+        NgramTable {
+            n,
+            floor: f64::from(floor),
+            log_probs,
+        }
+    }
+
+    /// Length of the n-grams in the table.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Log-probability given to n-grams that never appear in the corpus.
+    pub fn floor(&self) -> f64 {
+        self.floor
+    }
+
+    /// Iterates over the n-grams that are more likely than the floor, in alphabetical order,
+    /// along with their log-probability.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, f64)> + '_ {
+        self.log_probs
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| f64::from(**p) > self.floor)
+            .map(|(mut i, p)| {
+                let mut ngram = vec![b'a'; self.n];
+                for c in ngram.iter_mut().rev() {
+                    *c += (i % 26) as u8;
+                    i /= 26;
+                }
+                (ngram, f64::from(*p))
+            })
+    }
+
+    /// Position of `ngram` in the table, or `None` if it isn't made of exactly `n` letters.
+    /// Letters are folded first, so accented letters share the entry of their unaccented
+    /// equivalent.
+    fn index(&self, ngram: &[u8]) -> Option<usize> {
+        let letters = if ngram.is_ascii() {
+            ngram.to_vec()
+        } else {
+            String::from_utf8_lossy(ngram)
+                .chars()
+                .map(fold_letter)
+                .collect::<Option<Vec<_>>>()?
+        };
+
+        if letters.len() != self.n {
+            return None;
+        }
+
+        letters.iter().try_fold(0, |acc, c| {
+            c.is_ascii_alphabetic()
+                .then(|| acc * 26 + (c.to_ascii_lowercase() - b'a') as usize)
+        })
+    }
+
+    /// Log-probability of `ngram`. Anything that isn't a sequence of `n` letters gets the floor.
+    pub fn log_prob(&self, ngram: &[u8]) -> f64 {
+        self.index(ngram)
+            .map(|i| f64::from(self.log_probs[i]))
+            .unwrap_or(self.floor)
+    }
+
+    /// Sum of the log-probabilities of every n-gram of `letters`, which must only contain
+    /// lowercase ASCII letters.
+    fn folded_fitness(&self, letters: &[u8]) -> f64 {
+        letters
+            .windows(self.n)
+            .map(|w| {
+                let i = w.iter().fold(0, |acc, c| acc * 26 + (c - b'a') as usize);
+                f64::from(self.log_probs[i])
+            })
+            .sum()
+    }
+
+    /// Sum of the log-probabilities of every n-gram of `text`, after folding its letters and
+    /// dropping everything else. This is the fitness function used when hill climbing
+    /// substitution keys.
+    pub fn fitness(&self, text: &[u8]) -> f64 {
+        self.folded_fitness(&fold_letters(text))
+    }
+}
+
+/// Average log-probability per n-gram, so that texts of different lengths can be compared. Texts
+/// with fewer than `n` letters score the floor.
+impl Scorer for NgramTable {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        let letters = fold_letters(bytes);
+        if letters.len() < self.n {
+            return self.floor;
+        }
+
+        self.folded_fitness(&letters) / (letters.len() - self.n + 1) as f64
+    }
+}
+
+impl Scorer for &NgramTable {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        (*self).score(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_prob() {
+        assert!(QUADGRAM_LOG_PROB.log_prob(b"TION") > QUADGRAM_LOG_PROB.log_prob(b"QXZJ"));
+        assert_eq!(
+            QUADGRAM_LOG_PROB.floor(),
+            QUADGRAM_LOG_PROB.log_prob(b"qxzj")
+        );
+        assert_eq!(
+            QUADGRAM_LOG_PROB.floor(),
+            QUADGRAM_LOG_PROB.log_prob(b"th e")
+        );
+        assert!(BIGRAM_LOG_PROB.log_prob(b"th") > BIGRAM_LOG_PROB.log_prob(b"tq"));
+        assert_eq!(
+            TRIGRAM_LOG_PROB.log_prob(b"THE"),
+            TRIGRAM_LOG_PROB.log_prob(b"the")
+        );
+    }
+
+    #[test]
+    fn test_iter() {
+        let (ngram, p) = BIGRAM_LOG_PROB
+            .iter()
+            .find(|(ngram, _)| ngram == b"th")
+            .unwrap();
+        assert_eq!(BIGRAM_LOG_PROB.log_prob(&ngram), p);
+        assert!(BIGRAM_LOG_PROB
+            .iter()
+            .all(|(_, p)| p > BIGRAM_LOG_PROB.floor()));
+    }
+
+    #[test]
+    fn test_probabilities_sum_to_one() {
+        for table in [&*BIGRAM_LOG_PROB, &*TRIGRAM_LOG_PROB, &*QUADGRAM_LOG_PROB] {
+            let total = table
+                .log_probs
+                .iter()
+                .filter(|p| f64::from(**p) > table.floor)
+                .map(|p| 10_f64.powf(f64::from(*p)))
+                .sum::<f64>();
+            assert!((total - 1.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_score() {
+        let english = b"Now that the party is jumping";
+        let garbage = b"Ieeacdm*GI-y*fcao*k*zedn*el*hkied";
+        assert!(QUADGRAM_LOG_PROB.score(english) > QUADGRAM_LOG_PROB.score(garbage));
+        assert_eq!(QUADGRAM_LOG_PROB.floor(), QUADGRAM_LOG_PROB.score(b"ab"));
+    }
+}
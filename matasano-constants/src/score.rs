@@ -0,0 +1,309 @@
+//! Scoring functions that measure how much a sequence of bytes looks like English text.
+//!
+//! Every [`Scorer`] follows the same convention: the higher the score, the more likely it is
+//! that the input is English. Scorers based on distances return the negated distance.
+
+use std::collections::HashMap;
+
+use crate::language::Language;
+
+/// Proportion of letters in English prose. The remainder is split between spaces and everything
+/// else (digits, punctuation, new lines).
+const LETTER_SHARE: f64 = 0.8;
+const SPACE_SHARE: f64 = 0.17;
+const OTHER_SHARE: f64 = 0.03;
+
+/// Something that can rank candidate plaintexts.
+pub trait Scorer {
+    fn score(&self, bytes: &[u8]) -> f64;
+}
+
+impl<F: Fn(&[u8]) -> f64> Scorer for F {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        self(bytes)
+    }
+}
+
+/// Pearson's chi-squared test against the monogram frequencies of a language, where spaces and
+/// other characters have their own categories. Letters are case insensitive.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChiSquared {
+    language: Language,
+}
+
+impl ChiSquared {
+    pub fn new(language: Language) -> Self {
+        ChiSquared { language }
+    }
+}
+
+impl Scorer for ChiSquared {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.is_empty() {
+            return f64::NEG_INFINITY;
+        }
+
+        let monograms = self.language.model().monograms;
+        let mut counts = [0_usize; 256];
+        for byte in bytes {
+            counts[*byte as usize] += 1;
+        }
+
+        let letters = |c: u8| counts[c as usize] + counts[c.to_ascii_uppercase() as usize];
+        let spaces = counts[b' ' as usize];
+        let ascii_letters = (b'a'..=b'z').map(letters).sum::<usize>();
+        let ascii = counts[..128].iter().sum::<usize>();
+        let mut others = ascii - ascii_letters - spaces;
+        let mut n = ascii;
+
+        // Only languages with accented letters need the non-ASCII bytes decoded, otherwise they
+        // are all counted as other characters.
+        let mut accented = HashMap::<char, usize>::new();
+        if ascii < bytes.len() && monograms.keys().any(|c| !c.is_ascii()) {
+            for c in String::from_utf8_lossy(bytes)
+                .chars()
+                .filter(|c| !c.is_ascii())
+            {
+                let c = c.to_lowercase().next().unwrap_or(c);
+                if monograms.contains_key(&c) {
+                    *accented.entry(c).or_default() += 1;
+                } else {
+                    others += 1;
+                }
+                n += 1;
+            }
+        } else {
+            others += bytes.len() - ascii;
+            n = bytes.len();
+        }
+
+        let n = n as f64;
+        let chi2 = |observed: usize, p: f64| {
+            let expected = p * n;
+            (observed as f64 - expected).powi(2) / expected
+        };
+
+        let letters_chi2 = monograms
+            .iter()
+            .map(|(c, p)| {
+                let observed = if c.is_ascii_lowercase() {
+                    letters(*c as u8)
+                } else {
+                    accented.get(c).copied().unwrap_or_default()
+                };
+                chi2(observed, LETTER_SHARE * p)
+            })
+            .sum::<f64>();
+
+        -(letters_chi2 + chi2(spaces, SPACE_SHARE) + chi2(others, OTHER_SHARE))
+    }
+}
+
+/// Negated [`crate::bhattacharyya_distance`] between the letter distribution of the input and
+/// the monogram frequencies of a language. Letters are case insensitive and every other
+/// character is ignored, so an input without letters scores negative infinity.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bhattacharyya {
+    language: Language,
+}
+
+impl Bhattacharyya {
+    pub fn new(language: Language) -> Self {
+        Bhattacharyya { language }
+    }
+}
+
+impl Scorer for Bhattacharyya {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        let model = self.language.model();
+        let mut freq = HashMap::new();
+        let mut total = 0;
+
+        for c in String::from_utf8_lossy(bytes).chars() {
+            let c = c.to_lowercase().next().unwrap_or(c);
+            if model.monograms.contains_key(&c) {
+                *freq.entry(c).or_insert(0_f64) += 1.0;
+                total += 1;
+            }
+        }
+
+        for v in freq.values_mut() {
+            *v /= f64::from(total);
+        }
+
+        -model.bhattacharyya_distance(&freq)
+    }
+}
+
+/// Average frequency, according to the word frequencies of a language, of the whitespace
+/// separated words of the input. Words are case insensitive and leading or trailing punctuation
+/// is ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WordFrequency {
+    language: Language,
+}
+
+impl WordFrequency {
+    pub fn new(language: Language) -> Self {
+        WordFrequency { language }
+    }
+}
+
+impl Scorer for WordFrequency {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        let words = self.language.model().words;
+        let text = String::from_utf8_lossy(bytes).to_lowercase();
+        let (total, count) = text
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| c.is_ascii_punctuation()))
+            .fold((0_f64, 0), |(total, count), word| {
+                (
+                    total + words.get(word).copied().unwrap_or_default(),
+                    count + 1,
+                )
+            });
+
+        if count == 0 {
+            0.0
+        } else {
+            total / f64::from(count)
+        }
+    }
+}
+
+/// Fraction of the input made of printable ASCII characters and common whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrintableRatio;
+
+impl Scorer for PrintableRatio {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        if bytes.is_empty() {
+            return 0.0;
+        }
+
+        let printable = bytes
+            .iter()
+            .filter(|b| b.is_ascii_graphic() || matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+            .count();
+
+        printable as f64 / bytes.len() as f64
+    }
+}
+
+/// Weighted sum of other scorers.
+///
+/// ```
+/// use matasano_constants::score::{PrintableRatio, Scorer, Weighted, WordFrequency};
+///
+/// let scorer = Weighted::new()
+///     .with(1.0, PrintableRatio)
+///     .with(10.0, WordFrequency::default());
+/// assert!(scorer.score(b"the cat") > scorer.score(b"\x00\x01\x02"));
+/// ```
+#[derive(Default)]
+pub struct Weighted {
+    scorers: Vec<(f64, Box<dyn Scorer + Send + Sync>)>,
+}
+
+impl Weighted {
+    pub fn new() -> Self {
+        Weighted::default()
+    }
+
+    /// Adds `scorer`, whose score is multiplied by `weight`.
+    pub fn with(mut self, weight: f64, scorer: impl Scorer + Send + Sync + 'static) -> Self {
+        self.scorers.push((weight, Box::new(scorer)));
+        self
+    }
+
+    /// A combination that works well for ranking the output of XOR ciphers against English
+    /// plaintexts.
+    pub fn english() -> Self {
+        Weighted::for_language(Language::English)
+    }
+
+    /// Same as [`Weighted::english`], for plaintexts in another language.
+    pub fn for_language(language: Language) -> Self {
+        Weighted::new()
+            .with(1.0, ChiSquared::new(language))
+            .with(100.0, PrintableRatio)
+            .with(100.0, WordFrequency::new(language))
+    }
+}
+
+impl Scorer for Weighted {
+    fn score(&self, bytes: &[u8]) -> f64 {
+        self.scorers
+            .iter()
+            .map(|(weight, scorer)| weight * scorer.score(bytes))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WORD_FREQ;
+
+    const ENGLISH: &[u8] = b"Cooking MC's like a pound of bacon";
+    const GARBAGE: &[u8] = b"Ieeacdm*GI-y*fcao*k*zedn*el*hkied";
+
+    #[test]
+    fn test_scorers_prefer_english() {
+        let scorers: [&dyn Scorer; 5] = [
+            &ChiSquared::default(),
+            &Bhattacharyya::default(),
+            &WordFrequency::default(),
+            &PrintableRatio,
+            &Weighted::english(),
+        ];
+
+        for scorer in scorers {
+            assert!(scorer.score(ENGLISH) >= scorer.score(GARBAGE));
+            assert!(scorer.score(ENGLISH) > scorer.score(&[0xFF; 34]));
+        }
+    }
+
+    #[test]
+    fn test_chi_squared_non_ascii() {
+        // Without accented letters in the model, every non-ASCII byte is another character.
+        let english = ChiSquared::default();
+        assert_eq!(english.score(b"**"), english.score("é".as_bytes()));
+        assert_eq!(f64::NEG_INFINITY, english.score(b""));
+
+        let french = ChiSquared::new(Language::French);
+        assert_ne!(french.score(b"*"), french.score("é".as_bytes()));
+        assert_eq!(french.score("É".as_bytes()), french.score("é".as_bytes()));
+    }
+
+    #[test]
+    fn test_closure_scorer() {
+        let scorer = |bytes: &[u8]| bytes.len() as f64;
+        assert_eq!(3.0, scorer.score(b"abc"));
+    }
+
+    #[test]
+    fn test_printable_ratio() {
+        assert_eq!(0.5, PrintableRatio.score(b"ab\x00\x01"));
+        assert_eq!(0.0, PrintableRatio.score(b""));
+    }
+
+    #[test]
+    fn test_word_frequency() {
+        assert_eq!(
+            (WORD_FREQ["the"] + WORD_FREQ["of"]) / 3.0,
+            WordFrequency::default().score(b"The bottom, of")
+        );
+    }
+
+    #[test]
+    fn test_other_languages() {
+        let french = "Il était une fois une belle au bois dormant".as_bytes();
+        let scorer = Weighted::for_language(Language::French);
+        assert!(scorer.score(french) > scorer.score(b"Ieeacdm*GI-y*fcao*k*zedn*el*hkied"));
+        assert!(
+            Bhattacharyya::new(Language::French).score(french)
+                > Bhattacharyya::new(Language::German).score(french)
+        );
+    }
+}
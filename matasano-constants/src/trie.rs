@@ -0,0 +1,334 @@
+//! A compact trie of lowercase ASCII words. The build script compiles the word lists in
+//! `data/words` into this format, so that they can be embedded without being parsed at runtime.
+//!
+//! Nodes and edges are stored in two flat arrays. Every node points to a contiguous, sorted run
+//! of edges, and every edge holds a letter and the index of the node it leads to. Node 0 is the
+//! root.
+//!
+//! This file doesn't depend on the rest of the crate, because it's also compiled into the build
+//! script.
+
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    first_edge: u32,
+    edge_count: u8,
+    terminal: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Edge {
+    letter: u8,
+    node: u32,
+}
+
+/// A set of words that supports exact lookups, prefix queries and scanning text for words.
+///
+/// Words are stored lowercased and every query is ASCII case insensitive. Bytes other than ASCII
+/// letters never match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trie {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+    len: usize,
+}
+
+impl Trie {
+    /// Builds a trie from `words`. Words that contain anything other than ASCII letters, as well
+    /// as empty words, are skipped.
+    pub fn from_words<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut children = vec![BTreeMap::<u8, usize>::new()];
+        let mut terminal = vec![false];
+
+        for word in words {
+            let word = word.as_ref();
+            if word.is_empty() || !word.bytes().all(|b| b.is_ascii_alphabetic()) {
+                continue;
+            }
+
+            let mut node = 0;
+            for letter in word.bytes().map(|b| b.to_ascii_lowercase()) {
+                node = match children[node].get(&letter) {
+                    Some(next) => *next,
+                    None => {
+                        children.push(BTreeMap::new());
+                        terminal.push(false);
+                        let next = children.len() - 1;
+                        children[node].insert(letter, next);
+                        next
+                    }
+                };
+            }
+            terminal[node] = true;
+        }
+
+        let mut nodes = Vec::with_capacity(children.len());
+        let mut edges = Vec::new();
+        for (node_children, terminal) in children.iter().zip(&terminal) {
+            nodes.push(Node {
+                first_edge: edges.len() as u32,
+                edge_count: node_children.len() as u8,
+                terminal: *terminal,
+            });
+            edges.extend(node_children.iter().map(|(letter, node)| Edge {
+                letter: *letter,
+                node: *node as u32,
+            }));
+        }
+
+        let len = terminal.iter().filter(|t| **t).count();
+        Trie { nodes, edges, len }
+    }
+
+    /// Serializes the trie as little-endian `u32`s: the number of nodes, then two words per node
+    /// (index of the first edge, and the edge count with the terminal flag in bit 8), then one
+    /// word per edge (the index of the child node shifted left by 8, or'd with the letter).
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        let nodes = self.nodes.iter().flat_map(|node| {
+            [
+                node.first_edge,
+                u32::from(node.edge_count) | (u32::from(node.terminal) << 8),
+            ]
+        });
+        let edges = self
+            .edges
+            .iter()
+            .map(|edge| (edge.node << 8) | u32::from(edge.letter));
+
+        std::iter::once(self.nodes.len() as u32)
+            .chain(nodes)
+            .chain(edges)
+            .flat_map(u32::to_le_bytes)
+            .collect()
+    }
+
+    /// Reverse of [`Trie::to_le_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// If `bytes` isn't a serialized trie.
+    pub fn from_le_bytes(bytes: &[u8]) -> Self {
+        let mut words = bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+        let node_count = words.next().expect("empty trie") as usize;
+
+        let nodes = (0..node_count)
+            .map(|_| {
+                let first_edge = words.next().expect("truncated trie");
+                let flags = words.next().expect("truncated trie");
+                Node {
+                    first_edge,
+                    edge_count: flags as u8,
+                    terminal: flags & 0x100 != 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        let edges = words
+            .map(|word| Edge {
+                letter: word as u8,
+                node: word >> 8,
+            })
+            .collect();
+
+        let len = nodes.iter().filter(|node| node.terminal).count();
+        Trie { nodes, edges, len }
+    }
+
+    /// Number of words in the trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn child(&self, node: usize, letter: u8) -> Option<usize> {
+        let node = &self.nodes[node];
+        let start = node.first_edge as usize;
+        let edges = &self.edges[start..start + node.edge_count as usize];
+        let letter = letter.to_ascii_lowercase();
+
+        edges
+            .binary_search_by_key(&letter, |edge| edge.letter)
+            .ok()
+            .map(|i| edges[i].node as usize)
+    }
+
+    /// Node reached by following `prefix` from the root, if any.
+    fn walk(&self, prefix: &[u8]) -> Option<usize> {
+        prefix
+            .iter()
+            .try_fold(0, |node, letter| self.child(node, *letter))
+    }
+
+    /// Checks whether `word` is in the trie.
+    pub fn contains(&self, word: impl AsRef<[u8]>) -> bool {
+        let word = word.as_ref();
+        !word.is_empty()
+            && self
+                .walk(word)
+                .is_some_and(|node| self.nodes[node].terminal)
+    }
+
+    /// Checks whether any word of the trie starts with `prefix`.
+    pub fn has_prefix(&self, prefix: impl AsRef<[u8]>) -> bool {
+        self.walk(prefix.as_ref()).is_some()
+    }
+
+    /// Every word that starts with `prefix`, in alphabetical order.
+    pub fn words_with_prefix(&self, prefix: impl AsRef<[u8]>) -> Vec<String> {
+        let prefix = prefix.as_ref();
+        let mut res = Vec::new();
+
+        if let Some(node) = self.walk(prefix) {
+            let mut word = prefix.to_ascii_lowercase();
+            self.collect(node, &mut word, &mut res);
+        }
+
+        res
+    }
+
+    fn collect(&self, node: usize, word: &mut Vec<u8>, res: &mut Vec<String>) {
+        let n = &self.nodes[node];
+        if n.terminal {
+            res.push(String::from_utf8_lossy(word).into_owned());
+        }
+
+        let start = n.first_edge as usize;
+        for edge in &self.edges[start..start + n.edge_count as usize] {
+            word.push(edge.letter);
+            self.collect(edge.node as usize, word, res);
+            word.pop();
+        }
+    }
+
+    /// Length of the longest word that `text` starts with, if any.
+    pub fn longest_prefix_of(&self, text: impl AsRef<[u8]>) -> Option<usize> {
+        let mut node = 0;
+        let mut longest = None;
+
+        for (i, letter) in text.as_ref().iter().enumerate() {
+            match self.child(node, *letter) {
+                Some(next) => node = next,
+                None => break,
+            }
+            if self.nodes[node].terminal {
+                longest = Some(i + 1);
+            }
+        }
+
+        longest
+    }
+
+    /// Length of the longest word of the trie that starts at position `pos` of `text`, if any.
+    pub fn longest_word_at(&self, text: impl AsRef<[u8]>, pos: usize) -> Option<usize> {
+        text.as_ref()
+            .get(pos..)
+            .and_then(|rest| self.longest_prefix_of(rest))
+    }
+
+    /// Splits `text` into words, even if they aren't separated by spaces. At every position, the
+    /// scan takes the longest word that starts there, or skips one byte if there's none, so it
+    /// won't always find the best segmentation.
+    pub fn segment<'a>(&self, text: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut res = Vec::new();
+        let mut pos = 0;
+
+        while pos < text.len() {
+            match self.longest_word_at(text, pos) {
+                Some(len) => {
+                    res.push(&text[pos..pos + len]);
+                    pos += len;
+                }
+                None => pos += 1,
+            }
+        }
+
+        res
+    }
+
+    /// Fraction of the ASCII letters of `text` that are part of a word found by
+    /// [`Trie::segment`]. Zero if `text` has no letters.
+    pub fn word_coverage(&self, text: impl AsRef<[u8]>) -> f64 {
+        let text = text.as_ref();
+        let letters = text.iter().filter(|b| b.is_ascii_alphabetic()).count();
+        if letters == 0 {
+            return 0.0;
+        }
+
+        let covered = self.segment(text).iter().map(|w| w.len()).sum::<usize>();
+        covered as f64 / letters as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trie() -> Trie {
+        Trie::from_words([
+            "the", "them", "theme", "a", "party", "art", "is", "x-ray", "",
+        ])
+    }
+
+    #[test]
+    fn test_contains() {
+        let trie = trie();
+        assert_eq!(7, trie.len());
+        assert!(trie.contains("them"));
+        assert!(trie.contains("THEME"));
+        assert!(!trie.contains("th"));
+        assert!(!trie.contains("x-ray"));
+        assert!(!trie.contains(""));
+    }
+
+    #[test]
+    fn test_prefix() {
+        let trie = trie();
+        assert!(trie.has_prefix("th"));
+        assert!(!trie.has_prefix("tx"));
+        assert_eq!(vec!["the", "them", "theme"], trie.words_with_prefix("The"));
+        assert!(trie.words_with_prefix("b").is_empty());
+    }
+
+    #[test]
+    fn test_longest_word_at() {
+        let trie = trie();
+        assert_eq!(Some(4), trie.longest_word_at("xthemx", 1));
+        assert_eq!(Some(5), trie.longest_word_at("theme", 0));
+        assert_eq!(None, trie.longest_word_at("xthemx", 0));
+        assert_eq!(None, trie.longest_word_at("the", 10));
+    }
+
+    #[test]
+    fn test_segment() {
+        let trie = trie();
+        let words = trie.segment(b"thepartyisx");
+        assert_eq!(vec![&b"the"[..], b"party", b"is"], words);
+        assert_eq!(1.0, trie.word_coverage("the party"));
+        assert_eq!(0.5, trie.word_coverage("a q!"));
+        assert_eq!(0.0, trie.word_coverage("123"));
+    }
+
+    #[test]
+    fn test_english_words() {
+        let words = &*crate::ENGLISH_WORDS;
+        assert!(words.len() > 25_000);
+        assert!(words.contains("Bacon"));
+        assert!(words.word_coverage("nowthatthepartyisjumping") > 0.9);
+        assert!(words.word_coverage("qzxjvkwpfhgtdmrblcnsyiueao") < 0.5);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let trie = trie();
+        assert_eq!(trie, Trie::from_le_bytes(&trie.to_le_bytes()));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::Error;
+
 /// Takes an input that can be any sequence of bytes and returns a Vec of bytes
 /// with the input Base64 encoded.
 pub fn encode(input: impl AsRef<[u8]>) -> Vec<u8> {
@@ -38,49 +40,53 @@ pub fn encode(input: impl AsRef<[u8]>) -> Vec<u8> {
 }
 
 /// Takes as input a string which represents Base64 encoded data and returns a Vec
-/// of bytes with the decoded input.
-pub fn decode(input: impl AsRef<str>) -> Vec<u8> {
+/// of bytes with the decoded input. Fails if the length of the input isn't a multiple of 4, or if
+/// it has characters outside of the Base64 alphabet or padding anywhere but at the end.
+pub fn decode(input: impl AsRef<str>) -> Result<Vec<u8>, Error> {
     let input = input.as_ref().chars().collect::<Vec<_>>();
-    let len = input.len() * 6 / 8;
-    let mut res = Vec::with_capacity(len);
-    for chunk in input.chunks(4) {
-        match chunk {
-            [a, b, '=', '='] => {
-                let x = from_ascii(*a) & 0x3F;
-                let y = from_ascii(*b) & 0x30;
-                res.push((x << 2) | (y >> 4));
-            }
-            [a, b, c, '='] => {
-                let x = from_ascii(*a) & 0x3F;
-                let y = from_ascii(*b) & 0x3F;
-                let z = from_ascii(*c) & 0x3C;
-                res.push((x << 2) | (y >> 4));
-                res.push(((y & 0xF) << 4) | ((z & 0x3C) >> 2));
-            }
-            [a, b, c, d] => {
-                let x = from_ascii(*a) & 0x3F;
-                let y = from_ascii(*b) & 0x3F;
-                let z = from_ascii(*c) & 0x3F;
-                let w = from_ascii(*d) & 0x3F;
-                res.push((x << 2) | (y >> 4));
-                res.push(((y & 0xF) << 4) | ((z & 0x3C) >> 2));
-                res.push((z << 6) | w);
-            }
-            _ => unreachable!(),
+    if !input.len().is_multiple_of(4) {
+        return Err(Error::InvalidLength(input.len()));
+    }
+
+    let padding = match input.as_slice() {
+        [.., '=', '='] => 2,
+        [.., '='] => 1,
+        _ => 0,
+    };
+    let values = input[..input.len() - padding]
+        .iter()
+        .enumerate()
+        .map(|(index, c)| {
+            from_ascii(*c).ok_or(Error::InvalidCharacter {
+                index,
+                character: *c,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Thanks to the padding, the last group has at least 2 characters.
+    let mut res = Vec::with_capacity(values.len() * 6 / 8);
+    for chunk in values.chunks(4) {
+        res.push((chunk[0] << 2) | (chunk[1] >> 4));
+        if let Some(c) = chunk.get(2) {
+            res.push((chunk[1] << 4) | (c >> 2));
+        }
+        if let Some(d) = chunk.get(3) {
+            res.push((chunk[2] << 6) | d);
         }
     }
 
-    res
+    Ok(res)
 }
 
-const fn from_ascii(c: char) -> u8 {
+const fn from_ascii(c: char) -> Option<u8> {
     match c {
-        'A'..='Z' => c as u8 - 0x41,
-        'a'..='z' => c as u8 - 0x47,
-        '0'..='9' => c as u8 + 0x4,
-        '+' => 0x3E,
-        '/' => 0x3F,
-        _ => unreachable!(),
+        'A'..='Z' => Some(c as u8 - 0x41),
+        'a'..='z' => Some(c as u8 - 0x47),
+        '0'..='9' => Some(c as u8 + 0x4),
+        '+' => Some(0x3E),
+        '/' => Some(0x3F),
+        _ => None,
     }
 }
 
@@ -95,11 +101,13 @@ const PADDING: u8 = 0x3D;
 #[cfg(test)]
 mod tests {
     use super::*;
-    use matasano_util::hex_str_to_bytes;
 
     fn example_input() -> Vec<u8> {
         let input = "49276d206b696c6c696e6720796f757220627261696e206c696b65206120706f69736f6e6f7573206d757368726f6f6d";
-        hex_str_to_bytes(input)
+        (0..input.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&input[i..i + 2], 16).unwrap())
+            .collect()
     }
 
     #[test]
@@ -120,17 +128,26 @@ mod tests {
 
     #[test]
     fn test_decode() {
-        assert_eq!("wor".to_string(), String::from_utf8_lossy(&decode("d29y")));
+        assert_eq!(
+            "wor".to_string(),
+            String::from_utf8_lossy(&decode("d29y").unwrap())
+        );
     }
 
     #[test]
     fn test_decode_padding1() {
-        assert_eq!("wo".to_string(), String::from_utf8_lossy(&decode("d28=")));
+        assert_eq!(
+            "wo".to_string(),
+            String::from_utf8_lossy(&decode("d28=").unwrap())
+        );
     }
 
     #[test]
     fn test_decode_padding2() {
-        assert_eq!("w".to_string(), String::from_utf8_lossy(&decode("dw==")));
+        assert_eq!(
+            "w".to_string(),
+            String::from_utf8_lossy(&decode("dw==").unwrap())
+        );
     }
 
     #[test]
@@ -152,7 +169,28 @@ mod tests {
         let input = "Many hands make light work.".to_string();
         assert_eq!(
             input.clone(),
-            String::from_utf8_lossy(&decode(String::from_utf8_lossy(&encode(input))))
+            String::from_utf8_lossy(&decode(String::from_utf8_lossy(&encode(input))).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(Err(Error::InvalidLength(3)), decode("d29"));
+        assert_eq!(
+            Err(Error::InvalidCharacter {
+                index: 2,
+                character: '!'
+            }),
+            decode("d2!y")
+        );
+        assert_eq!(
+            Err(Error::InvalidCharacter {
+                index: 3,
+                character: '='
+            }),
+            decode("d28=d29y")
         );
+        assert!(decode("====").is_err());
+        assert_eq!(Ok(Vec::new()), decode(""));
     }
 }
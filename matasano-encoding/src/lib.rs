@@ -1 +1,25 @@
+use std::{error, fmt};
+
 pub mod base64;
+
+/// Error while decoding malformed input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The length of the input, which isn't a multiple of the size of a group of characters.
+    InvalidLength(usize),
+    /// A character that isn't part of the alphabet, or padding before the end of the input.
+    InvalidCharacter { index: usize, character: char },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength(len) => write!(f, "invalid length: {}", len),
+            Error::InvalidCharacter { index, character } => {
+                write!(f, "invalid character {:?} at index {}", character, index)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {}
@@ -0,0 +1,306 @@
+//! `matasano`: the encodings, XOR tools and statistics of this workspace as shell commands.
+//!
+//! Every command reads its input from a file, or from stdin if there's none (or it's `-`), and
+//! writes its result to stdout, so that commands can be chained:
+//!
+//! ```text
+//! matasano b64 dec 6.txt | matasano break-xor repeating
+//! ```
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use anyhow::{bail, Context};
+use matasano_bitwise::break_xor;
+use matasano_constants::{
+    byte_freq::ByteFrequency,
+    language::Language,
+    score::{Bhattacharyya, ChiSquared, PrintableRatio, Scorer, Weighted, WordFrequency},
+};
+use matasano_util::data::{self, Format};
+
+const USAGE: &str = "\
+Usage: matasano <command> [<file>]
+
+Commands:
+  hex enc|dec                 Encode to or decode from hex
+  b64 enc|dec                 Encode to or decode from Base64
+  xor --key <text>            XOR with a repeating key
+  xor --key-hex <hex>         Same, with the key given in hex
+  break-xor single|repeating  Break single-byte or repeating-key XOR. Prints the plaintext, and
+                              the key to stderr
+  score                       Score the input as English plaintext, with every scorer
+  freq                        Count every byte value, most frequent first
+  hexdump                     Print a canonical hex+ASCII dump
+
+Reads <file>, or stdin if it's missing or `-`, and writes to stdout.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    HexEncode,
+    HexDecode,
+    Base64Encode,
+    Base64Decode,
+    Xor(Vec<u8>),
+    BreakSingleByteXor,
+    BreakRepeatingKeyXor,
+    Score,
+    Freq,
+    Hexdump,
+}
+
+/// Parses the arguments, without the name of the binary, into the command and the input file.
+fn parse(args: impl IntoIterator<Item = String>) -> anyhow::Result<(Command, Option<String>)> {
+    let mut args = args.into_iter();
+    let name = args.next().context("missing command")?;
+    let mut next = |what: &str| {
+        args.next()
+            .with_context(|| format!("{} needs {}", name, what))
+    };
+
+    let command = match name.as_str() {
+        "hex" | "b64" => match (name.as_str(), next("enc or dec")?.as_str()) {
+            ("hex", "enc") => Command::HexEncode,
+            ("hex", "dec") => Command::HexDecode,
+            ("b64", "enc") => Command::Base64Encode,
+            ("b64", "dec") => Command::Base64Decode,
+            (_, mode) => bail!("unknown mode for {}: {}", name, mode),
+        },
+        "xor" => match next("--key or --key-hex")?.as_str() {
+            "--key" => Command::Xor(next("a key")?.into_bytes()),
+            "--key-hex" => Command::Xor(data::decode(
+                "--key-hex",
+                next("a key")?.as_bytes(),
+                Format::HexLines,
+            )?),
+            arg => bail!("unexpected argument: {}", arg),
+        },
+        "break-xor" => match next("single or repeating")?.as_str() {
+            "single" => Command::BreakSingleByteXor,
+            "repeating" => Command::BreakRepeatingKeyXor,
+            mode => bail!("unknown mode for break-xor: {}", mode),
+        },
+        "score" => Command::Score,
+        "freq" => Command::Freq,
+        "hexdump" => Command::Hexdump,
+        _ => bail!("unknown command: {}", name),
+    };
+
+    if let Command::Xor(key) = &command {
+        if key.is_empty() {
+            bail!("the key can't be empty");
+        }
+    }
+
+    let file = args.next().filter(|file| file != "-");
+    if let Some(arg) = args.next() {
+        bail!("unexpected argument: {}", arg);
+    }
+
+    Ok((command, file))
+}
+
+/// Runs `command` on `input`, whose name is `origin` for error messages. Additional information,
+/// such as the key found when breaking XOR, goes to `info`.
+fn execute(
+    command: &Command,
+    origin: &str,
+    input: &[u8],
+    out: &mut impl Write,
+    info: &mut impl Write,
+) -> anyhow::Result<()> {
+    match command {
+        Command::HexEncode => writeln!(out, "{}", matasano_util::bytes_to_hex_str(input))?,
+        Command::HexDecode => out.write_all(&data::decode(origin, input, Format::HexLines)?)?,
+        Command::Base64Encode => {
+            out.write_all(&matasano_encoding::base64::encode(input))?;
+            writeln!(out)?;
+        }
+        Command::Base64Decode => out.write_all(&data::decode(origin, input, Format::Base64)?)?,
+        Command::Xor(key) => out.write_all(&matasano_bitwise::xor_cycle(input, key))?,
+        Command::BreakSingleByteXor => {
+            let (key, plaintext, _) =
+                break_xor::rank_single_byte_xor(input, &Weighted::english(), 1).remove(0);
+            writeln!(info, "key: {:#04x}", key)?;
+            out.write_all(&plaintext)?;
+        }
+        Command::BreakRepeatingKeyXor => {
+            let (key, plaintext, _) =
+                break_xor::break_repeating_key_xor(input, &Weighted::english())
+                    .into_iter()
+                    .next()
+                    .context("the input is too short to guess the key size")?;
+            writeln!(info, "key: {:?}", String::from_utf8_lossy(&key))?;
+            out.write_all(&plaintext)?;
+        }
+        Command::Score => {
+            let scorers: [(&str, &dyn Scorer); 7] = [
+                ("weighted", &Weighted::english()),
+                ("chi-squared", &ChiSquared::default()),
+                ("bhattacharyya", &Bhattacharyya::default()),
+                ("word-frequency", &WordFrequency::default()),
+                ("printable", &PrintableRatio),
+                ("byte-frequency", &ByteFrequency::default()),
+                ("quadgrams", Language::English.model().ngrams()),
+            ];
+            for (name, scorer) in scorers {
+                writeln!(out, "{:<16}{:.4}", name, scorer.score(input))?;
+            }
+        }
+        Command::Freq => {
+            let mut counts = [0_usize; 256];
+            for b in input {
+                counts[*b as usize] += 1;
+            }
+
+            let mut bytes = (0..=u8::MAX)
+                .filter(|b| counts[*b as usize] > 0)
+                .collect::<Vec<_>>();
+            bytes.sort_by_key(|b| std::cmp::Reverse(counts[*b as usize]));
+
+            for b in bytes {
+                let c = if b.is_ascii_graphic() { b as char } else { '.' };
+                let count = counts[b as usize];
+                let percent = 100.0 * count as f64 / input.len() as f64;
+                writeln!(out, "{:#04x}  {}  {:>8}  {:6.2}%", b, c, count, percent)?;
+            }
+        }
+        Command::Hexdump => write!(out, "{}", matasano_util::hexdump(input))?,
+    }
+
+    Ok(())
+}
+
+fn run(args: impl IntoIterator<Item = String>) -> anyhow::Result<()> {
+    let (command, file) = parse(args)?;
+
+    let (origin, input) = match file {
+        Some(file) => {
+            let input = fs::read(&file).with_context(|| format!("can't read {}", file))?;
+            (file, input)
+        }
+        None => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input)?;
+            ("<stdin>".to_string(), input)
+        }
+    };
+
+    let mut out = io::stdout().lock();
+    execute(&command, &origin, &input, &mut out, &mut io::stderr())?;
+    out.flush()?;
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    // Only in the command position, so that `-h` can still be a file name or a key.
+    if matches!(
+        args.first().map(String::as_str),
+        None | Some("-h" | "--help")
+    ) {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    fn execute_str(command: &Command, input: &[u8]) -> (Vec<u8>, String) {
+        let (mut out, mut info) = (Vec::new(), Vec::new());
+        execute(command, "test", input, &mut out, &mut info).unwrap();
+        (out, String::from_utf8(info).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            (Command::HexEncode, None),
+            parse(args("hex enc -")).unwrap()
+        );
+        assert_eq!(
+            (Command::Xor(b"ICE".to_vec()), Some("in.bin".to_string())),
+            parse(args("xor --key ICE in.bin")).unwrap()
+        );
+        assert_eq!(
+            Command::Xor(vec![0xde, 0xad]),
+            parse(args("xor --key-hex dead")).unwrap().0
+        );
+        assert!(parse(args("hex encode")).is_err());
+        assert!(parse(args("xor --key-hex xyz")).is_err());
+        assert!(parse(args("freq a b")).is_err());
+        assert!(parse(args("frobnicate")).is_err());
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(
+            b"4d616e\n".to_vec(),
+            execute_str(&Command::HexEncode, b"Man").0
+        );
+        assert_eq!(
+            b"Man".to_vec(),
+            execute_str(&Command::HexDecode, b"4d61\n6e\n").0
+        );
+        assert_eq!(
+            b"TWFu\n".to_vec(),
+            execute_str(&Command::Base64Encode, b"Man").0
+        );
+        assert_eq!(
+            b"Man".to_vec(),
+            execute_str(&Command::Base64Decode, b"TWFu\r\n").0
+        );
+
+        let err = execute(
+            &Command::HexDecode,
+            "test",
+            b"4d\nzz\n",
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            "test:2: invalid hex: invalid hex digit 'z' at index 0",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_break_xor() {
+        let ciphertext = matasano_util::hex_str_to_bytes(
+            "1b37373331363f78151b7f2b783431333d78397828372d363c78373e783a393b3736",
+        )
+        .unwrap();
+        let (plaintext, info) = execute_str(&Command::BreakSingleByteXor, &ciphertext);
+
+        assert_eq!(b"Cooking MC's like a pound of bacon".to_vec(), plaintext);
+        assert_eq!("key: 0x58\n", info);
+    }
+
+    #[test]
+    fn test_freq() {
+        let (out, _) = execute_str(&Command::Freq, b"abracadabra");
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(Some("0x61  a         5   45.45%"), out.lines().next());
+        assert_eq!(5, out.lines().count());
+    }
+}
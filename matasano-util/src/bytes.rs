@@ -0,0 +1,256 @@
+use std::{
+    fmt,
+    ops::{BitXor, BitXorAssign, Deref, DerefMut},
+    slice::Chunks,
+    str::FromStr,
+};
+
+use crate::Error;
+
+/// Owned sequence of bytes that prints as hex, parses from hex and dumps itself with
+/// [`hexdump`](crate::hexdump) when debugged.
+///
+/// ```
+/// use matasano_util::Bytes;
+///
+/// let a: Bytes = "1c0111001f010100061a024b53535009181c".parse()?;
+/// let b: Bytes = "686974207468652062756c6c277320657965".parse()?;
+/// assert_eq!(
+///     "746865206b696420646f6e277420706c6179",
+///     (&a ^ &b).to_string()
+/// );
+/// # Ok::<(), matasano_util::Error>(())
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(Vec<u8>);
+
+impl Bytes {
+    pub fn new() -> Self {
+        Bytes(Vec::new())
+    }
+
+    pub fn from_base64(s: impl AsRef<str>) -> Result<Self, Error> {
+        Ok(Bytes(matasano_encoding::base64::decode(s)?))
+    }
+
+    pub fn to_base64(&self) -> String {
+        // Base64 is ASCII.
+        matasano_encoding::base64::encode(&self.0)
+            .into_iter()
+            .map(char::from)
+            .collect()
+    }
+
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Blocks of `size` bytes, the last one being shorter if `size` doesn't divide the length.
+    /// Panics if `size` is 0.
+    pub fn blocks(&self, size: usize) -> Chunks<'_, u8> {
+        assert!(size > 0, "block size must not be 0");
+        self.0.chunks(size)
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for Bytes {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl From<Vec<u8>> for Bytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Bytes {
+    fn from(bytes: &[u8]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Bytes {
+    fn from(bytes: [u8; N]) -> Self {
+        Bytes(bytes.to_vec())
+    }
+}
+
+impl From<Bytes> for Vec<u8> {
+    fn from(bytes: Bytes) -> Self {
+        bytes.0
+    }
+}
+
+impl FromIterator<u8> for Bytes {
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        Bytes(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Bytes {
+    type Item = &'a u8;
+    type IntoIter = std::slice::Iter<'a, u8>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// Parses hex, like [`hex_str_to_bytes`](crate::hex_str_to_bytes).
+impl FromStr for Bytes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Ok(Bytes(crate::hex_str_to_bytes(s)?))
+    }
+}
+
+/// Lowercase hex.
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl fmt::UpperHex for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        self.0.iter().try_for_each(|b| write!(f, "{:02X}", b))
+    }
+}
+
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&crate::hexdump(&self.0))
+    }
+}
+
+// The XOR operators behave like `matasano_bitwise::xor` and stop at the end of the shorter
+// operand. They can't call it, since `matasano-bitwise` depends on this crate.
+
+impl BitXor for &Bytes {
+    type Output = Bytes;
+
+    fn bitxor(self, rhs: &Bytes) -> Bytes {
+        self.0.iter().zip(&rhs.0).map(|(a, b)| a ^ b).collect()
+    }
+}
+
+impl BitXor for Bytes {
+    type Output = Bytes;
+
+    fn bitxor(self, rhs: Bytes) -> Bytes {
+        &self ^ &rhs
+    }
+}
+
+/// XOR with a single byte.
+impl BitXor<u8> for &Bytes {
+    type Output = Bytes;
+
+    fn bitxor(self, rhs: u8) -> Bytes {
+        self.0.iter().map(|b| b ^ rhs).collect()
+    }
+}
+
+impl BitXorAssign<&Bytes> for Bytes {
+    fn bitxor_assign(&mut self, rhs: &Bytes) {
+        self.0.truncate(rhs.len());
+        self.0.iter_mut().zip(&rhs.0).for_each(|(a, b)| *a ^= b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        let bytes: Bytes = "00DEadbeef".parse().unwrap();
+
+        assert_eq!(Bytes::from([0x00, 0xde, 0xad, 0xbe, 0xef]), bytes);
+        assert_eq!("00deadbeef", bytes.to_string());
+        assert_eq!("0x00deadbeef", format!("{:#x}", bytes));
+        assert_eq!("00DEADBEEF", format!("{:X}", bytes));
+        assert!("dea".parse::<Bytes>().is_err());
+    }
+
+    #[test]
+    fn test_base64() {
+        let bytes = Bytes::from(b"Man".to_vec());
+
+        assert_eq!("TWFu", bytes.to_base64());
+        assert_eq!(bytes, Bytes::from_base64("TWFu").unwrap());
+        assert!(Bytes::from_base64("TWF").is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        assert_eq!(
+            "00000000  4d 61 6e                                          |Man|\n00000003\n",
+            format!("{:?}", Bytes::from(*b"Man"))
+        );
+    }
+
+    #[test]
+    fn test_xor() {
+        let a = Bytes::from([0x0f, 0xf0, 0xff]);
+        let b = Bytes::from([0xff, 0xff]);
+
+        assert_eq!(Bytes::from([0xf0, 0x0f]), &a ^ &b);
+        assert_eq!(Bytes::from([0xf0, 0x0f, 0x00]), &a ^ 0xff);
+
+        let mut c = a.clone();
+        c ^= &b;
+        assert_eq!(&a ^ &b, c);
+        assert_eq!(a.clone() ^ b.clone(), c);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let bytes = (0..10).collect::<Bytes>();
+        let blocks = bytes.blocks(4).collect::<Vec<_>>();
+
+        assert_eq!(vec![&[0, 1, 2, 3][..], &[4, 5, 6, 7], &[8, 9]], blocks);
+        assert_eq!(10, bytes.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "block size must not be 0")]
+    fn test_blocks_zero() {
+        let _ = Bytes::from([1, 2, 3]).blocks(0);
+    }
+}
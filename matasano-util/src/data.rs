@@ -0,0 +1,294 @@
+//! Loading of challenge data files, decoded according to their format.
+//!
+//! Text formats accept both LF and CRLF line endings, and ignore blank lines as well as leading
+//! and trailing whitespace on every line. Files can be read at runtime with [`load`] and
+//! [`load_records`], or embedded in the binary with [`include_data!`](crate::include_data) and
+//! [`include_records!`](crate::include_records).
+
+use std::{
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// How the contents of a data file are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One hex encoded record per line.
+    HexLines,
+    /// One Base64 encoded record per line.
+    Base64Lines,
+    /// A single Base64 encoded blob, possibly wrapped over several lines.
+    Base64,
+    /// The bytes of the file as they are.
+    Raw,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    /// The file isn't in any of these directories.
+    NotFound(Vec<PathBuf>),
+    /// A record that isn't valid hex, with the reason.
+    InvalidHex(crate::HexError),
+    /// A record that isn't valid Base64, with the reason.
+    InvalidBase64(matasano_encoding::Error),
+}
+
+/// Error while loading a data file, with the file and the line (starting at 1) it happened on.
+#[derive(Debug)]
+pub struct Error {
+    origin: String,
+    line: Option<usize>,
+    kind: ErrorKind,
+}
+
+impl Error {
+    /// Path of the file, or name of the embedded data.
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.origin)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+
+        match &self.kind {
+            ErrorKind::Io(err) => write!(f, ": {}", err),
+            ErrorKind::NotFound(dirs) => {
+                write!(f, ": not found, searched")?;
+                for (i, dir) in dirs.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", sep, dir.join(&self.origin).display())?;
+                }
+                Ok(())
+            }
+            ErrorKind::InvalidHex(err) => write!(f, ": invalid hex: {}", err),
+            ErrorKind::InvalidBase64(err) => write!(f, ": invalid Base64: {}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            ErrorKind::NotFound(_) => None,
+            ErrorKind::InvalidHex(err) => Some(err),
+            ErrorKind::InvalidBase64(err) => Some(err),
+        }
+    }
+}
+
+/// Non-blank lines of `text` with their line numbers, trimmed.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// Decodes `contents`, the data called `origin`, into its records. [`Format::Base64`] and
+/// [`Format::Raw`] always give a single record.
+///
+/// This is what [`load_records`] does once the file is read, and it's meant for data that was
+/// embedded at compile time.
+pub fn decode_records(
+    origin: &str,
+    contents: &[u8],
+    format: Format,
+) -> Result<Vec<Vec<u8>>, Error> {
+    let error = |line, kind| Error {
+        origin: origin.to_string(),
+        line,
+        kind,
+    };
+    // Only the text formats have to be UTF-8.
+    let text = || {
+        std::str::from_utf8(contents).map_err(|err| {
+            error(
+                None,
+                ErrorKind::Io(io::Error::new(io::ErrorKind::InvalidData, err)),
+            )
+        })
+    };
+
+    match format {
+        Format::HexLines => lines(text()?)
+            .map(|(n, line)| {
+                crate::hex_str_to_bytes(line)
+                    .map_err(|err| error(Some(n), ErrorKind::InvalidHex(err)))
+            })
+            .collect(),
+        Format::Base64Lines => lines(text()?)
+            .map(|(n, line)| {
+                matasano_encoding::base64::decode(line)
+                    .map_err(|err| error(Some(n), ErrorKind::InvalidBase64(err)))
+            })
+            .collect(),
+        Format::Base64 => {
+            let blob = lines(text()?).map(|(_, line)| line).collect::<String>();
+
+            matasano_encoding::base64::decode(blob)
+                .map(|bytes| vec![bytes])
+                .map_err(|err| error(None, ErrorKind::InvalidBase64(err)))
+        }
+        Format::Raw => Ok(vec![contents.to_vec()]),
+    }
+}
+
+/// Same as [`decode_records`], with the records concatenated.
+pub fn decode(origin: &str, contents: &[u8], format: Format) -> Result<Vec<u8>, Error> {
+    decode_records(origin, contents, format).map(|records| records.concat())
+}
+
+/// First of `dirs` that contains the file `name`, joined with it.
+pub fn find<P: AsRef<Path>>(
+    name: impl AsRef<Path>,
+    dirs: impl IntoIterator<Item = P>,
+) -> Result<PathBuf, Error> {
+    let name = name.as_ref();
+    let dirs = dirs
+        .into_iter()
+        .map(|dir| dir.as_ref().to_path_buf())
+        .collect::<Vec<_>>();
+
+    dirs.iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| Error {
+            origin: name.display().to_string(),
+            line: None,
+            kind: ErrorKind::NotFound(dirs),
+        })
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|err| Error {
+        origin: path.display().to_string(),
+        line: None,
+        kind: ErrorKind::Io(err),
+    })
+}
+
+/// Reads the file at `path` and decodes it into its records.
+///
+/// ```no_run
+/// use matasano_util::data::{self, Format};
+///
+/// let ciphertexts = data::load_records("data/4.txt", Format::HexLines)?;
+/// # Ok::<(), data::Error>(())
+/// ```
+pub fn load_records(path: impl AsRef<Path>, format: Format) -> Result<Vec<Vec<u8>>, Error> {
+    let path = path.as_ref();
+    decode_records(&path.display().to_string(), &read(path)?, format)
+}
+
+/// Reads the file at `path` and decodes it, with the records concatenated.
+pub fn load(path: impl AsRef<Path>, format: Format) -> Result<Vec<u8>, Error> {
+    load_records(path, format).map(|records| records.concat())
+}
+
+/// Embeds a data file in the binary with `include_bytes!` and decodes it at runtime, like
+/// [`data::decode`](crate::data::decode). The path is relative to the file that uses the macro.
+#[macro_export]
+macro_rules! include_data {
+    ($path:literal, $format:expr) => {
+        $crate::data::decode($path, include_bytes!($path), $format)
+    };
+}
+
+/// Same as [`include_data!`], with the records kept apart like
+/// [`data::decode_records`](crate::data::decode_records).
+#[macro_export]
+macro_rules! include_records {
+    ($path:literal, $format:expr) => {
+        $crate::data::decode_records($path, include_bytes!($path), $format)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_lines() {
+        assert_eq!(
+            vec![vec![0xde, 0xad], vec![0xbe, 0xef]],
+            decode_records("test", b"dead\r\n\r\n  beef\r\n", Format::HexLines).unwrap()
+        );
+
+        let err = decode_records("test", b"dead\nbeefy\n", Format::HexLines).unwrap_err();
+        assert_eq!(Some(2), err.line());
+        assert_eq!(
+            "test:2: invalid hex: invalid hex digit 'y' at index 4",
+            err.to_string()
+        );
+        assert!(error::Error::source(&err).is_some());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(
+            vec![b"any".to_vec(), b"an".to_vec()],
+            decode_records("test", b"YW55\nYW4=\n", Format::Base64Lines).unwrap()
+        );
+        assert_eq!(
+            b"anyanan".to_vec(),
+            decode("test", b"YW55\r\nYW5h\r\nbg==\r\n", Format::Base64).unwrap()
+        );
+        assert!(decode("test", b"YW4=\nYW55\n", Format::Base64).is_err());
+        assert_eq!(
+            "test:1: invalid Base64: invalid character '!' at index 3",
+            decode("test", b"YW5!\n", Format::Base64Lines)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_raw() {
+        assert_eq!(
+            b"\r\n\x00".to_vec(),
+            decode("test", b"\r\n\x00", Format::Raw).unwrap()
+        );
+        assert_eq!(
+            vec![vec![0xff, 0xfe]],
+            decode_records("test", b"\xff\xfe", Format::Raw).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let err = load("does/not/exist.txt", Format::Raw).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+        assert!(err.to_string().starts_with("does/not/exist.txt: "));
+    }
+
+    #[test]
+    fn test_find() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        assert_eq!(
+            Path::new(dir).join("Cargo.toml"),
+            find("Cargo.toml", ["/does/not/exist", dir]).unwrap()
+        );
+
+        let err = find("missing.txt", ["a", "b"]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::NotFound(dirs) if dirs.len() == 2));
+        assert_eq!(
+            "missing.txt: not found, searched a/missing.txt, b/missing.txt",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use std::{error, fmt, io};
+
+use crate::data;
+
+/// Error of the fallible functions of the workspace, so that malformed data never makes a
+/// library panic.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Hex(HexError),
+    Base64(matasano_encoding::Error),
+    Data(data::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Hex(err) => write!(f, "{}", err),
+            Error::Base64(err) => write!(f, "invalid Base64: {}", err),
+            Error::Data(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Hex(err) => Some(err),
+            Error::Base64(err) => Some(err),
+            Error::Data(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<HexError> for Error {
+    fn from(err: HexError) -> Self {
+        Error::Hex(err)
+    }
+}
+
+impl From<matasano_encoding::Error> for Error {
+    fn from(err: matasano_encoding::Error) -> Self {
+        Error::Base64(err)
+    }
+}
+
+impl From<data::Error> for Error {
+    fn from(err: data::Error) -> Self {
+        Error::Data(err)
+    }
+}
+
+/// Error while decoding a malformed hex string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexError {
+    /// The number of digits, which is odd.
+    OddLength(usize),
+    /// A character that isn't a hex digit.
+    InvalidDigit { index: usize, character: char },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::OddLength(len) => write!(f, "odd number of hex digits: {}", len),
+            HexError::InvalidDigit { index, character } => {
+                write!(f, "invalid hex digit {:?} at index {}", character, index)
+            }
+        }
+    }
+}
+
+impl error::Error for HexError {}
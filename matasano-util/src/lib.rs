@@ -1,53 +1,89 @@
-use std::{fs::File, io::Read, path::Path};
+mod bytes;
+pub mod data;
+mod error;
+pub mod par;
+pub mod rng;
+
+use std::{fs, path::Path};
+
+pub use bytes::Bytes;
+pub use error::{Error, HexError};
 
 /// Takes a string with hexadecimal characters and returns a Vec with the bytes represented by
-/// those characters.
+/// those characters. Fails if a character isn't a hex digit, or if there's an odd number of them.
 ///
-/// ```text
+/// ```
 /// assert_eq!(
 ///     vec![0xde, 0xad, 0xbe, 0xef],
-///     hex_str_to_bytes("deadbeef")
+///     matasano_util::hex_str_to_bytes("deadbeef")?
 /// );
+/// # Ok::<(), matasano_util::HexError>(())
 /// ```
-pub fn hex_str_to_bytes(xs: &str) -> Vec<u8> {
-    #[inline]
-    fn b_to_dec(b: u8) -> u8 {
-        match b {
-            48..=57 => b - 48,
-            97..=102 => b - 97 + 10,
-            65..=70 => b - 65 + 10,
-            _ => unreachable!(),
-        }
+pub fn hex_str_to_bytes(xs: &str) -> Result<Vec<u8>, HexError> {
+    let digits = xs
+        .char_indices()
+        .map(|(index, character)| {
+            character
+                .to_digit(16)
+                .map(|d| d as u8)
+                .ok_or(HexError::InvalidDigit { index, character })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !digits.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(digits.len()));
     }
 
-    let mut res = Vec::new();
-    let mut iter = xs.bytes();
+    Ok(digits.chunks(2).map(|d| (d[0] << 4) | d[1]).collect())
+}
+
+/// Inverse of [`hex_str_to_bytes`]: returns the lowercase hexadecimal representation of `bytes`.
+pub fn bytes_to_hex_str(bytes: impl AsRef<[u8]>) -> String {
+    bytes
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
 
-    loop {
-        let a = iter.next();
-        let b = iter.next();
+/// Canonical hex+ASCII dump of `bytes`, like `hexdump -C`: 16 bytes per line, preceded by their
+/// offset and followed by their printable characters. The last line is the total length.
+pub fn hexdump(bytes: impl AsRef<[u8]>) -> String {
+    let bytes = bytes.as_ref();
+    let mut res = String::new();
 
-        match (a, b) {
-            (None, None) => {
-                return res;
-            }
-            (Some(aa), Some(bb)) => {
-                let aaa = b_to_dec(aa);
-                let bbb = b_to_dec(bb);
-                res.push(((aaa << 4) & 0xF0) | (bbb & 0xF));
+    for (i, line) in bytes.chunks(16).enumerate() {
+        let mut hex = String::with_capacity(49);
+        for (j, b) in line.iter().enumerate() {
+            if j == 8 {
+                hex.push(' ');
             }
-            _ => unreachable!(),
+            hex.push_str(&format!("{:02x} ", b));
         }
+        let ascii = line
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect::<String>();
+
+        res.push_str(&format!("{:08x}  {:<49} |{}|\n", i * 16, hex, ascii));
     }
-}
+    res.push_str(&format!("{:08x}\n", bytes.len()));
 
-pub fn get_file_contents(name: impl AsRef<Path>) -> ::std::io::Result<Vec<String>> {
-    let mut buffer = String::new();
-    let mut file = File::open(name)?;
+    res
+}
 
-    file.read_to_string(&mut buffer).unwrap();
+/// Lines of the text file `name`, without the surrounding whitespace of the file. Fails if the
+/// file can't be read or isn't valid UTF-8.
+pub fn get_file_contents(name: impl AsRef<Path>) -> Result<Vec<String>, Error> {
+    let buffer = fs::read_to_string(name)?;
 
-    Ok(buffer.trim().split("\n").map(String::from).collect())
+    Ok(buffer.trim().lines().map(String::from).collect())
 }
 
 #[cfg(test)]
@@ -61,7 +97,56 @@ mod tests {
                 0x1c, 0x1, 0x11, 0x0, 0x1f, 0x1, 0x1, 0x0, 0x6, 0x1a, 0x2, 0x4b, 0x53, 0x53, 0x50,
                 0x9, 0x18, 0x1c
             ],
-            hex_str_to_bytes("1c0111001f010100061a024b53535009181c")
+            hex_str_to_bytes("1c0111001f010100061a024b53535009181c").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hex_errors() {
+        assert_eq!(Err(HexError::OddLength(3)), hex_str_to_bytes("dea"));
+        assert_eq!(
+            Err(HexError::InvalidDigit {
+                index: 3,
+                character: 'z'
+            }),
+            hex_str_to_bytes("deaz")
+        );
+        assert_eq!(
+            "invalid hex digit 'z' at index 3",
+            Error::from(hex_str_to_bytes("deaz").unwrap_err()).to_string()
+        );
+        assert_eq!(Vec::<u8>::new(), hex_str_to_bytes("").unwrap());
+    }
+
+    #[test]
+    fn test_get_file_contents() {
+        assert!(matches!(
+            get_file_contents("does/not/exist.txt"),
+            Err(Error::Io(_))
+        ));
+        let lines = get_file_contents(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap();
+        assert_eq!("[package]", lines[0]);
+    }
+
+    #[test]
+    fn test_hexdump() {
+        assert_eq!(
+            "00000000  49 27 6d 20 6b 69 6c 6c  69 6e 67 20 79 6f 75 72  |I'm killing \
+             your|\n00000010  00 0a                                             |..|\n00000012\n",
+            hexdump(b"I'm killing your\x00\n")
+        );
+        assert_eq!("00000000\n", hexdump([]));
+    }
+
+    #[test]
+    fn test_bytes_to_hex_str() {
+        assert_eq!(
+            "00deadbeef",
+            bytes_to_hex_str([0x00, 0xde, 0xad, 0xbe, 0xef])
+        );
+        assert_eq!(
+            vec![0x1c, 0x01, 0xff],
+            hex_str_to_bytes(&bytes_to_hex_str([0x1c, 0x01, 0xff])).unwrap()
         );
     }
 }
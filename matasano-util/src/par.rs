@@ -0,0 +1,90 @@
+//! Data parallelism over slices with scoped threads, for embarrassingly parallel workloads such
+//! as trying every key or every line of a file.
+//!
+//! The slice is split in as many contiguous chunks as there are available cores, and every chunk
+//! is processed by its own thread. Results always come back in the order of the input, so they
+//! don't depend on the number of threads or on scheduling.
+
+use std::{cmp::Ordering, num::NonZeroUsize, thread};
+
+/// Number of threads used for `len` items.
+fn threads(len: usize) -> usize {
+    thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+        .min(len)
+        .max(1)
+}
+
+/// Applies `f` to every item of `items` in parallel, and returns the results in the same order as
+/// the items.
+pub fn par_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads(items.len());
+    if threads == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_len = items.len().div_ceil(threads);
+    let f = &f;
+
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
+}
+
+/// Applies `f` to every item of `items` in parallel, and returns the index and result of the
+/// maximum result according to `compare`. Ties go to the first item, so the result is
+/// deterministic. Returns `None` if `items` is empty.
+pub fn par_max_by<T, R, F, C>(items: &[T], f: F, mut compare: C) -> Option<(usize, R)>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    C: FnMut(&R, &R) -> Ordering,
+{
+    par_map(items, f)
+        .into_iter()
+        .enumerate()
+        .reduce(|best, item| match compare(&item.1, &best.1) {
+            Ordering::Greater => item,
+            _ => best,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_map() {
+        let items = (0..1000_u64).collect::<Vec<_>>();
+        assert_eq!(
+            items.iter().map(|x| x * x).collect::<Vec<_>>(),
+            par_map(&items, |x| x * x)
+        );
+        assert_eq!(Vec::<u64>::new(), par_map(&[], |x: &u64| *x));
+    }
+
+    #[test]
+    fn test_par_max_by() {
+        let items = [3, 7, 1, 7, 5];
+        assert_eq!(
+            Some((1, 7)),
+            par_max_by(&items, |x| *x, |a: &i32, b| a.cmp(b))
+        );
+        assert_eq!(None, par_max_by(&[], |x: &i32| *x, |a, b| a.cmp(b)));
+    }
+}
@@ -0,0 +1,191 @@
+//! Sources of randomness for oracles, key generation and randomised solvers.
+//!
+//! [`OsRng`] reads the entropy of the operating system from `/dev/urandom`, and is what oracles
+//! should use outside of tests. [`XorShift`] is a small deterministic generator: seeded with the
+//! same value, it always gives the same sequence, which keeps tests and solvers reproducible.
+//! Neither pulls in a dependency.
+//!
+//! Code that needs randomness should take an `&mut impl Rng`, so that callers can pick either.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    ops::Range,
+};
+
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn random_bytes(&mut self, n: usize) -> Vec<u8> {
+        let mut res = vec![0; n];
+        self.fill_bytes(&mut res);
+        res
+    }
+
+    /// Uniformly distributed integer in `range`. Panics if `range` is empty.
+    fn random_range(&mut self, range: Range<usize>) -> usize {
+        assert!(!range.is_empty(), "empty range");
+        let n = (range.end - range.start) as u64;
+
+        // Values from `zone` up would make the lowest results more likely than the others.
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return range.start + (x % n) as usize;
+            }
+        }
+    }
+
+    /// A coin flip.
+    fn random_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Uniformly distributed float in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// A random item of `items`, or `None` if it's empty.
+    fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T>
+    where
+        Self: Sized,
+    {
+        (!items.is_empty()).then(|| &items[self.random_range(0..items.len())])
+    }
+
+    /// Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T])
+    where
+        Self: Sized,
+    {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.random_range(0..i + 1));
+        }
+    }
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        (**self).fill_bytes(buf)
+    }
+}
+
+/// Entropy of the operating system, read from `/dev/urandom`.
+#[derive(Debug)]
+pub struct OsRng(File);
+
+impl OsRng {
+    pub fn new() -> io::Result<Self> {
+        File::open("/dev/urandom").map(OsRng)
+    }
+}
+
+/// Panics if `/dev/urandom` can't be read, which doesn't happen once it's open.
+impl Rng for OsRng {
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.fill_bytes(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, buf: &mut [u8]) {
+        self.0
+            .read_exact(buf)
+            .expect("failed to read from /dev/urandom");
+    }
+}
+
+/// Marsaglia's xorshift64 generator. Fast and statistically decent, but predictable: it must not
+/// be used where an attacker shouldn't guess the output.
+#[derive(Debug, Clone)]
+pub struct XorShift(u64);
+
+impl XorShift {
+    /// Generator whose state is `seed` mixed with the SplitMix64 finaliser, so that close seeds
+    /// give unrelated sequences.
+    pub fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        // The state must never be zero, or every output would be zero.
+        XorShift(if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z })
+    }
+}
+
+impl Rng for XorShift {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xorshift() {
+        assert_eq!(8_247_328_468_710_148_152, XorShift::new(1).next_u64());
+        assert_eq!(
+            XorShift::new(42).random_bytes(20),
+            XorShift::new(42).random_bytes(20)
+        );
+        assert_ne!(
+            XorShift::new(42).random_bytes(20),
+            XorShift::new(43).random_bytes(20)
+        );
+        assert_ne!(
+            XorShift::new(0).random_bytes(20),
+            XorShift::new(1).random_bytes(20)
+        );
+        assert_ne!(0, XorShift::new(0).next_u64());
+    }
+
+    #[test]
+    fn test_helpers() {
+        let mut rng = XorShift::new(7);
+
+        assert_eq!(13, rng.random_bytes(13).len());
+        assert!((0..1000).all(|_| (5..8).contains(&rng.random_range(5..8))));
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&rng.next_f64())));
+        assert_eq!(None, rng.choose::<u8>(&[]));
+        assert_eq!(Some(&3), rng.choose(&[3]));
+
+        let mut items = (0..50).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+        assert_ne!((0..50).collect::<Vec<_>>(), items);
+        items.sort();
+        assert_eq!((0..50).collect::<Vec<_>>(), items);
+
+        let heads = (0..1000).filter(|_| rng.random_bool()).count();
+        assert!((400..600).contains(&heads));
+    }
+
+    #[test]
+    fn test_os_rng() {
+        let mut rng = OsRng::new().unwrap();
+        assert_ne!(rng.random_bytes(16), rng.random_bytes(16));
+
+        fn draw(mut rng: impl Rng) -> usize {
+            rng.random_range(10..20)
+        }
+        assert!((10..20).contains(&draw(&mut rng)));
+    }
+}